@@ -9,11 +9,7 @@ import {
   resolvePseudoSelector,
   isResponsiveValue,
 } from '../utils/css-helpers.js'
-import {
-  generateClassName,
-  generatePseudoClassName,
-  generateResponsiveClassName,
-} from './class-name.js'
+import { generateClassName } from './class-name.js'
 import type { GeneratedCSS, PluginOptions } from '../types.js'
 
 /**
@@ -41,6 +37,35 @@ export function generateAtomicCSS(
   const cssRules = new Map<string, string>()
   const classNames: string[] = []
 
+  collectAtomicCSS(styles, options, '', cssRules, classNames)
+
+  return {
+    classNames,
+    cssRules,
+    className: classNames.join(' '),
+  }
+}
+
+/**
+ * Collect class names and rules for a style object
+ *
+ * @param styles - Style object
+ * @param options - Plugin options
+ * @param pseudo - Pseudo-selector shorthand the styles are nested under (e.g. '_hover'), or ''
+ * @param cssRules - Class name → rule, filled in place
+ * @param classNames - Class names in order, filled in place
+ */
+function collectAtomicCSS(
+  styles: Record<string, any>,
+  options: PluginOptions,
+  pseudo: string,
+  cssRules: Map<string, string>,
+  classNames: string[]
+): void {
+  // Pseudo styles are named with their variant (`silk_hover_bg_red_…`) like the SWC plugin
+  const pseudoName = pseudo.slice(1)
+  const selector = pseudo ? resolvePseudoSelector(pseudo) : ''
+
   for (const [property, value] of Object.entries(styles)) {
     // Skip undefined/null values
     if (value === undefined || value === null) {
@@ -49,16 +74,7 @@ export function generateAtomicCSS(
 
     // Handle pseudo-selectors
     if (isPseudoSelector(property)) {
-      const pseudo = resolvePseudoSelector(property)
-      const nestedResult = generateAtomicCSS(value, options)
-
-      for (const [cls, rule] of nestedResult.cssRules) {
-        // Inject pseudo-selector into rule
-        const pseudoRule = rule.replace(/\{/, `${pseudo} {`)
-        cssRules.set(cls, pseudoRule)
-        classNames.push(cls)
-      }
-
+      collectAtomicCSS(value, options, property, cssRules, classNames)
       continue
     }
 
@@ -67,23 +83,19 @@ export function generateAtomicCSS(
       const breakpoints = options.breakpoints || DEFAULT_BREAKPOINTS
 
       for (const [breakpoint, val] of Object.entries(value)) {
-        const className = generateResponsiveClassName(
-          breakpoint,
-          property,
-          val,
-          options
-        )
+        const variant = pseudoName ? `${pseudoName}_${breakpoint}` : breakpoint
+        const className = generateClassName(property, val, options, variant)
         const cssProperty = resolveCSSProperty(property)
         const cssValue = normalizeCSSValue(property, val, options.tokens)
 
         let rule: string
         if (breakpoint === 'base') {
           // Base styles (no media query)
-          rule = `.${className} { ${cssProperty}: ${cssValue}; }`
+          rule = `.${className}${selector} { ${cssProperty}: ${cssValue}; }`
         } else {
           // Media query for breakpoint
           const mediaQuery = breakpoints[breakpoint] || breakpoint
-          rule = `@media (min-width: ${mediaQuery}) { .${className} { ${cssProperty}: ${cssValue}; } }`
+          rule = `@media (min-width: ${mediaQuery}) { .${className}${selector} { ${cssProperty}: ${cssValue}; } }`
         }
 
         cssRules.set(className, rule)
@@ -94,20 +106,14 @@ export function generateAtomicCSS(
     }
 
     // Regular property
-    const className = generateClassName(property, value, options)
+    const className = generateClassName(property, value, options, pseudoName)
     const cssProperty = resolveCSSProperty(property)
     const cssValue = normalizeCSSValue(property, value, options.tokens)
-    const rule = `.${className} { ${cssProperty}: ${cssValue}; }`
+    const rule = `.${className}${selector} { ${cssProperty}: ${cssValue}; }`
 
     cssRules.set(className, rule)
     classNames.push(className)
   }
-
-  return {
-    classNames,
-    cssRules,
-    className: classNames.join(' '),
-  }
 }
 
 /**
@@ -0,0 +1,46 @@
+/**
+ * Class name golden corpora shared with the SWC plugin
+ * (packages/swc-plugin/tests/integration.rs runs the same fixtures with `classNaming: "babel"`)
+ */
+
+import { describe, it, expect } from 'vitest'
+import { generateClassName } from '../src/generators/class-name'
+import { generateAtomicCSS } from '../src/generators/css-generator'
+import type { PluginOptions } from '../src/types'
+import corpus from '../../swc-plugin/tests/fixtures/class-names.json'
+import cssCalls from '../../swc-plugin/tests/fixtures/css-calls.json'
+
+interface ClassNameCase {
+  property: string
+  value: string | number
+  variant: string
+  options: PluginOptions
+  expected: string
+}
+
+interface CssCallCase {
+  styles: Record<string, any>
+  options: PluginOptions
+  classNames: string[]
+  cssRules: [string, string][]
+}
+
+describe('class name golden corpus', () => {
+  it.each(corpus as ClassNameCase[])(
+    '$property: $value ($variant) → $expected',
+    ({ property, value, variant, options, expected }) => {
+      expect(generateClassName(property, value, options, variant)).toBe(expected)
+    }
+  )
+})
+
+describe('css() golden corpus', () => {
+  it.each(cssCalls as CssCallCase[])(
+    '$classNames',
+    ({ styles, options, classNames, cssRules }) => {
+      const result = generateAtomicCSS(styles, options)
+      expect(result.classNames).toEqual(classNames)
+      expect(Array.from(result.cssRules)).toEqual(cssRules)
+    }
+  )
+})
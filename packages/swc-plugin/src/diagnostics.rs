@@ -0,0 +1,152 @@
+//! Structured diagnostics reported through SWC's diagnostic handler
+//!
+//! Every part of a css() call that cannot be compiled gets a diagnostic with a
+//! stable code and the span of the offending property, instead of being skipped.
+
+use swc_core::common::{
+    errors::{DiagnosticId, HANDLER},
+    Span,
+};
+
+/// Stable diagnostic codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiagnosticCode {
+    /// SILK001: dynamic value left to the css() runtime
+    DynamicValue,
+    /// SILK002: spread that cannot be resolved at build time
+    DynamicSpread,
+    /// SILK003: computed key that cannot be resolved at build time
+    DynamicKey,
+    /// SILK004: css() called with something other than one object literal
+    UnsupportedArguments,
+    /// SILK005: unsupported nesting (e.g. a pseudo-selector inside a pseudo-selector)
+    UnsupportedNesting,
+    /// SILK006: breakpoint missing from `breakpoints`
+    UnknownBreakpoint,
+    /// SILK007: plugin config that does not match [`crate::Config`]
+    InvalidConfig,
+    /// SILK008: value or property name that would break out of its CSS declaration
+    InvalidValue,
+    /// SILK009: two declarations generated the same class name
+    ClassCollision,
+    /// SILK010: reference to a design token missing from `tokens`
+    UnknownToken,
+}
+
+impl DiagnosticCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiagnosticCode::DynamicValue => "SILK001",
+            DiagnosticCode::DynamicSpread => "SILK002",
+            DiagnosticCode::DynamicKey => "SILK003",
+            DiagnosticCode::UnsupportedArguments => "SILK004",
+            DiagnosticCode::UnsupportedNesting => "SILK005",
+            DiagnosticCode::UnknownBreakpoint => "SILK006",
+            DiagnosticCode::InvalidConfig => "SILK007",
+            DiagnosticCode::InvalidValue => "SILK008",
+            DiagnosticCode::ClassCollision => "SILK009",
+            DiagnosticCode::UnknownToken => "SILK010",
+        }
+    }
+}
+
+/// Diagnostic severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// A problem found while compiling a css() call
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub code: DiagnosticCode,
+    pub severity: Severity,
+    pub message: String,
+    pub span: Span,
+}
+
+impl Diagnostic {
+    pub fn warning(code: DiagnosticCode, span: Span, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            severity: Severity::Warning,
+            message: message.into(),
+            span,
+        }
+    }
+
+    pub fn error(code: DiagnosticCode, span: Span, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            severity: Severity::Error,
+            message: message.into(),
+            span,
+        }
+    }
+
+    /// Report through SWC's diagnostic handler (no-op when no handler is installed)
+    pub fn emit(&self) {
+        if !HANDLER.is_set() {
+            return;
+        }
+
+        HANDLER.with(|handler| {
+            let code = DiagnosticId::Error(self.code.as_str().to_string());
+            let message = format!("[silk] {}", self.message);
+            match self.severity {
+                Severity::Warning => handler.struct_span_warn_with_code(self.span, &message, code),
+                Severity::Error => handler.struct_span_err_with_code(self.span, &message, code),
+            }
+            .emit();
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use swc_core::common::{errors::Handler, DUMMY_SP};
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_diagnostic_codes_are_stable() {
+        assert_eq!(DiagnosticCode::DynamicValue.as_str(), "SILK001");
+        assert_eq!(DiagnosticCode::UnknownBreakpoint.as_str(), "SILK006");
+    }
+
+    #[test]
+    fn test_emit_through_handler() {
+        let buffer = Buffer::default();
+        let handler = Handler::with_emitter_writer(Box::new(buffer.clone()), None);
+
+        HANDLER.set(&handler, || {
+            Diagnostic::warning(DiagnosticCode::DynamicValue, DUMMY_SP, "dynamic value").emit();
+            Diagnostic::error(DiagnosticCode::DynamicSpread, DUMMY_SP, "dynamic spread").emit();
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("warning[SILK001]: [silk] dynamic value"), "{}", output);
+        assert!(output.contains("error[SILK002]: [silk] dynamic spread"), "{}", output);
+        assert_eq!(handler.err_count(), 1);
+    }
+
+    #[test]
+    fn test_emit_without_handler_is_noop() {
+        Diagnostic::warning(DiagnosticCode::DynamicValue, DUMMY_SP, "dynamic value").emit();
+    }
+}
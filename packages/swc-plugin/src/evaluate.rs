@@ -0,0 +1,285 @@
+//! Compile-time evaluation of static style values
+//!
+//! Covers the subset of Babel's `path.evaluate()` that shows up in style objects:
+//! literals, template literals, string concatenation, arithmetic, parentheses,
+//! TypeScript wrappers and identifiers bound to module-level `const` declarations.
+
+use std::collections::HashMap;
+use swc_core::ecma::ast::*;
+
+/// Maximum nesting before evaluation gives up (guards against `const a = b, b = a`)
+const MAX_DEPTH: usize = 32;
+
+/// Module-level `const` bindings that style values may reference
+#[derive(Debug, Clone, Default)]
+pub struct ModuleConstants {
+    bindings: HashMap<Id, Box<Expr>>,
+}
+
+impl ModuleConstants {
+    /// Collect top-level `const` declarations (including `export const`).
+    /// Only initializers that can be part of a style are kept, so components and
+    /// other large values are not copied.
+    pub fn collect(module: &Module) -> Self {
+        let mut bindings = HashMap::new();
+
+        for item in &module.body {
+            let var = match item {
+                ModuleItem::Stmt(Stmt::Decl(Decl::Var(var))) => var,
+                ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+                    decl: Decl::Var(var),
+                    ..
+                })) => var,
+                _ => continue,
+            };
+
+            if var.kind != VarDeclKind::Const {
+                continue;
+            }
+
+            for decl in &var.decls {
+                match (&decl.name, &decl.init) {
+                    (Pat::Ident(name), Some(init)) if is_style_expr(init) => {
+                        bindings.insert(name.to_id(), init.clone());
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Self { bindings }
+    }
+
+    /// Look up the initializer of a module-level `const`
+    pub fn get(&self, ident: &Ident) -> Option<&Expr> {
+        self.bindings.get(&ident.to_id()).map(|init| &**init)
+    }
+}
+
+/// Whether an expression is made only of parts [`evaluate`] and style extraction understand:
+/// literals, templates, unary and binary expressions, objects of those, identifiers and wrappers
+fn is_style_expr(expr: &Expr) -> bool {
+    match expr {
+        Expr::Lit(Lit::Str(_) | Lit::Num(_)) | Expr::Ident(_) => true,
+        Expr::Tpl(tpl) => tpl.exprs.iter().all(|expr| is_style_expr(expr)),
+        Expr::Unary(UnaryExpr { arg, .. }) => is_style_expr(arg),
+        Expr::Bin(BinExpr { left, right, .. }) => is_style_expr(left) && is_style_expr(right),
+        Expr::Object(obj) => obj.props.iter().all(|prop| match prop {
+            PropOrSpread::Spread(spread) => is_style_expr(&spread.expr),
+            PropOrSpread::Prop(prop) => match &**prop {
+                Prop::KeyValue(kv) => {
+                    let key = match &kv.key {
+                        PropName::Computed(computed) => is_style_expr(&computed.expr),
+                        _ => true,
+                    };
+                    key && is_style_expr(&kv.value)
+                }
+                _ => false,
+            },
+        }),
+        Expr::Paren(ParenExpr { expr, .. })
+        | Expr::TsAs(TsAsExpr { expr, .. })
+        | Expr::TsConstAssertion(TsConstAssertion { expr, .. })
+        | Expr::TsSatisfies(TsSatisfiesExpr { expr, .. })
+        | Expr::TsNonNull(TsNonNullExpr { expr, .. })
+        | Expr::TsTypeAssertion(TsTypeAssertion { expr, .. }) => is_style_expr(expr),
+        _ => false,
+    }
+}
+
+/// A value known at compile time
+#[derive(Debug, Clone, PartialEq)]
+pub enum StaticValue {
+    Str(String),
+    Num(f64),
+}
+
+impl StaticValue {
+    /// Convert to a style value string, the way JavaScript's `String(value)` does
+    pub fn into_style_value(self) -> String {
+        match self {
+            StaticValue::Str(s) => s,
+            StaticValue::Num(n) => format_number(n),
+        }
+    }
+}
+
+/// Evaluate a static expression, or `None` if it depends on runtime values
+pub fn evaluate(expr: &Expr, constants: &ModuleConstants) -> Option<StaticValue> {
+    evaluate_at_depth(expr, constants, 0)
+}
+
+/// Resolve identifiers and TypeScript/paren wrappers down to the underlying expression
+pub fn unwrap_static_expr<'a>(expr: &'a Expr, constants: &'a ModuleConstants) -> &'a Expr {
+    let mut expr = expr;
+
+    for _ in 0..MAX_DEPTH {
+        expr = match expr {
+            Expr::Paren(ParenExpr { expr, .. })
+            | Expr::TsAs(TsAsExpr { expr, .. })
+            | Expr::TsConstAssertion(TsConstAssertion { expr, .. })
+            | Expr::TsSatisfies(TsSatisfiesExpr { expr, .. })
+            | Expr::TsNonNull(TsNonNullExpr { expr, .. })
+            | Expr::TsTypeAssertion(TsTypeAssertion { expr, .. }) => expr,
+            Expr::Ident(ident) => match constants.get(ident) {
+                Some(init) => init,
+                None => return expr,
+            },
+            _ => return expr,
+        };
+    }
+
+    expr
+}
+
+fn evaluate_at_depth(expr: &Expr, constants: &ModuleConstants, depth: usize) -> Option<StaticValue> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    let depth = depth + 1;
+
+    match unwrap_static_expr(expr, constants) {
+        Expr::Lit(Lit::Str(s)) => Some(StaticValue::Str(s.value.as_str()?.to_string())),
+        Expr::Lit(Lit::Num(n)) => Some(StaticValue::Num(n.value)),
+
+        // Template literals: `${GAP}px`
+        Expr::Tpl(tpl) => {
+            let mut result = String::new();
+            for (i, quasi) in tpl.quasis.iter().enumerate() {
+                result.push_str(quasi.cooked.as_ref()?.as_str()?);
+                if let Some(expr) = tpl.exprs.get(i) {
+                    result.push_str(&evaluate_at_depth(expr, constants, depth)?.into_style_value());
+                }
+            }
+            Some(StaticValue::Str(result))
+        }
+
+        // Signed numbers: -4, +1
+        Expr::Unary(UnaryExpr { op, arg, .. }) => {
+            let StaticValue::Num(n) = evaluate_at_depth(arg, constants, depth)? else {
+                return None;
+            };
+            match op {
+                UnaryOp::Minus => Some(StaticValue::Num(-n)),
+                UnaryOp::Plus => Some(StaticValue::Num(n)),
+                _ => None,
+            }
+        }
+
+        // Concatenation and arithmetic: GAP * 2, SIZE + 'px'
+        Expr::Bin(BinExpr { op, left, right, .. }) => {
+            let left = evaluate_at_depth(left, constants, depth)?;
+            let right = evaluate_at_depth(right, constants, depth)?;
+
+            let result = match (op, left, right) {
+                (BinaryOp::Add, StaticValue::Num(a), StaticValue::Num(b)) => StaticValue::Num(a + b),
+                (BinaryOp::Add, a, b) => {
+                    StaticValue::Str(format!("{}{}", a.into_style_value(), b.into_style_value()))
+                }
+                (op, StaticValue::Num(a), StaticValue::Num(b)) => StaticValue::Num(match op {
+                    BinaryOp::Sub => a - b,
+                    BinaryOp::Mul => a * b,
+                    BinaryOp::Div => a / b,
+                    BinaryOp::Mod => a % b,
+                    BinaryOp::Exp => a.powf(b),
+                    _ => return None,
+                }),
+                _ => return None,
+            };
+
+            // NaN and Infinity are never meaningful style values
+            match result {
+                StaticValue::Num(n) if !n.is_finite() => None,
+                result => Some(result),
+            }
+        }
+
+        _ => None,
+    }
+}
+
+/// Format a number the way JavaScript's `String(n)` does for style values
+pub(crate) fn format_number(n: f64) -> String {
+    // `String(-0)` is "0" in JavaScript
+    if n == 0.0 {
+        return "0".to_string();
+    }
+    n.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swc_core::common::DUMMY_SP;
+
+    fn num(n: f64) -> Box<Expr> {
+        Box::new(Expr::Lit(Lit::Num(Number {
+            span: DUMMY_SP,
+            value: n,
+            raw: None,
+        })))
+    }
+
+    fn str_lit(s: &str) -> Box<Expr> {
+        Box::new(Expr::Lit(Lit::Str(Str {
+            span: DUMMY_SP,
+            value: s.into(),
+            raw: None,
+        })))
+    }
+
+    fn bin(op: BinaryOp, left: Box<Expr>, right: Box<Expr>) -> Expr {
+        Expr::Bin(BinExpr {
+            span: DUMMY_SP,
+            op,
+            left,
+            right,
+        })
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(4.0), "4");
+        assert_eq!(format_number(-0.0), "0");
+        assert_eq!(format_number(-0.5), "-0.5");
+    }
+
+    #[test]
+    fn test_evaluate_arithmetic() {
+        let constants = ModuleConstants::default();
+
+        let expr = bin(BinaryOp::Mul, num(4.0), num(2.0));
+        assert_eq!(evaluate(&expr, &constants), Some(StaticValue::Num(8.0)));
+
+        let expr = bin(BinaryOp::Add, num(4.0), str_lit("px"));
+        assert_eq!(evaluate(&expr, &constants), Some(StaticValue::Str("4px".into())));
+
+        // Division by zero is not a style value
+        let expr = bin(BinaryOp::Div, num(1.0), num(0.0));
+        assert_eq!(evaluate(&expr, &constants), None);
+
+        // Only string concatenation is allowed for strings
+        let expr = bin(BinaryOp::Sub, str_lit("a"), num(1.0));
+        assert_eq!(evaluate(&expr, &constants), None);
+    }
+
+    #[test]
+    fn test_is_style_expr() {
+        assert!(is_style_expr(&bin(BinaryOp::Mul, num(4.0), num(2.0))));
+        assert!(is_style_expr(&Expr::Object(ObjectLit {
+            span: DUMMY_SP,
+            props: vec![PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(IdentName::new("p".into(), DUMMY_SP)),
+                value: num(4.0),
+            })))],
+        })));
+
+        // Components and calls are never part of a style
+        let arrow = Expr::Arrow(ArrowExpr {
+            body: Box::new(BlockStmtOrExpr::Expr(num(1.0))),
+            ..Default::default()
+        });
+        assert!(!is_style_expr(&arrow));
+        assert!(!is_style_expr(&bin(BinaryOp::Add, num(1.0), Box::new(arrow))));
+    }
+}
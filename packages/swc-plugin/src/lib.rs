@@ -4,10 +4,29 @@ use swc_core::{
         visit::{VisitMut, VisitMutWith},
     },
     plugin::{plugin_transform, proxies::TransformPluginProgramMetadata},
-    common::DUMMY_SP,
+    common::{
+        comments::{Comment, CommentKind, Comments},
+        SourceMapper, Span, Spanned, DUMMY_SP,
+    },
 };
-use serde::Deserialize;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+mod diagnostics;
+mod evaluate;
+mod tokens;
+
+pub use diagnostics::{Diagnostic, DiagnosticCode, Severity};
+pub use evaluate::{evaluate, ModuleConstants, StaticValue};
+pub use tokens::{Token, TokenOutput};
+
+/// Plugin version reported in emitted CSS metadata
+const PLUGIN_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Marker that starts the CSS metadata comment emitted for each module.
+/// Loaders look for `/* @silk-css {...} */` and parse the JSON payload.
+pub const CSS_COMMENT_MARKER: &str = "@silk-css";
 
 /// Plugin configuration options
 #[derive(Debug, Clone, Deserialize)]
@@ -17,24 +36,453 @@ pub struct Config {
     #[serde(default)]
     pub production: bool,
 
-    /// Class name prefix
-    #[serde(default = "default_prefix")]
-    pub class_prefix: String,
+    /// Class name prefix. Unset means `silk`, except for Babel production names,
+    /// which fall back to `s` like the Babel plugin does
+    #[serde(default)]
+    pub class_prefix: Option<String>,
+
+    /// Class naming scheme
+    #[serde(default)]
+    pub class_naming: ClassNaming,
+
+    /// How class names are built (default: `hash` in production, `readable` otherwise)
+    #[serde(default)]
+    pub class_name_strategy: Option<ClassNameStrategy>,
+
+    /// Class name template such as `"[prefix]-[prop]-[hash:6]"`, overriding the strategy
+    /// - `[prefix]`, `[prop]`, `[value]`, `[variant]`: naming parts (`[value]` shortened)
+    /// - `[hash]`, `[hash:N]`: declaration hash, optionally its first N characters
+    /// - `[index]`: shortest identifier for the declaration's position in its module
+    #[serde(default)]
+    pub class_name_template: Option<String>,
+
+    /// Namespace mixed into every class name hash (and shown in development names),
+    /// so independently built apps on one page never share class names
+    #[serde(default)]
+    pub hash_salt: String,
+
+    /// Name classes after the emitted declaration rather than the written one,
+    /// so aliases like `bg`/`bgColor`/`backgroundColor` share a class
+    #[serde(default)]
+    pub dedupe_by_declaration: bool,
+
+    /// How collected CSS is handed to the bundler
+    #[serde(default)]
+    pub css_output: CssOutput,
+
+    /// Specifier pattern for `cssOutput: "import"`
+    /// - `[hash]`: content hash of the module's CSS
+    /// - `[css]`: URI-encoded CSS, for resolvers that read it from the specifier
+    #[serde(default = "default_import_pattern")]
+    pub import_pattern: String,
+
+    /// Import sources to transform
+    #[serde(default = "default_import_sources")]
+    pub import_sources: Vec<String>,
+
+    /// Functions to transform
+    #[serde(default = "default_functions")]
+    pub functions: Vec<String>,
+
+    /// Breakpoint name → min-width for responsive values
+    #[serde(default = "default_breakpoints")]
+    pub breakpoints: HashMap<String, String>,
+
+    /// Design tokens (`{"colors": {"primary": "#3b82f6"}}`), referenced from style
+    /// values as `$colors.primary` or `colors.primary`
+    #[serde(default)]
+    pub tokens: HashMap<String, Token>,
+
+    /// Whether token references compile to their value or to `var(--colors-primary)`
+    #[serde(default)]
+    pub token_output: TokenOutput,
+
+    /// Scale for numeric and named values of spacing properties. Unset, margin,
+    /// padding and gap use `0.25rem` steps and positional properties stay in `px`.
+    #[serde(default)]
+    pub spacing: Option<Spacing>,
+
+    /// Fail the build on css() calls that cannot be fully compiled
+    #[serde(default)]
+    pub strict: bool,
+
+    /// Glob patterns of files to transform (empty: every file)
+    #[serde(default)]
+    pub include: Vec<String>,
+
+    /// Glob patterns of files to skip, checked after `include`
+    #[serde(default = "default_exclude")]
+    pub exclude: Vec<String>,
+}
+
+/// Class naming scheme
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClassNaming {
+    /// Production hashes truncated to 8 characters, leading digits mapped to g–p
+    #[default]
+    Compact,
+
+    /// Exactly what babel-plugin-silk generates, so mixed Babel/SWC builds share class names.
+    /// Production names are `{classPrefix || "s"}{hash}`.
+    Babel,
+}
+
+/// How class names are built
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ClassNameStrategy {
+    /// Short hashed names (`oqmaqr`)
+    Hash,
+
+    /// Descriptive names (`silk_bg_red_oqmaqr`)
+    Readable,
+
+    /// Names in order of appearance after a 7-character module id (`k3xa9bqa`, `k3xa9bqb`).
+    /// Not shorter than hashed names, but a module's names share a prefix that compresses
+    /// well; the same declaration in two modules gets two classes.
+    SequentialMinimal,
+}
+
+/// Spacing scale for margin, padding, gap, inset and top/right/bottom/left
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Spacing {
+    /// Numbers are multiplied by this (`p: 4` → `1rem` with the default `0.25`)
+    #[serde(default = "default_spacing_multiplier")]
+    pub multiplier: f64,
+
+    /// Unit appended to multiplied numbers
+    #[serde(default = "default_spacing_unit")]
+    pub unit: String,
+
+    /// Named or numeric steps (`xs` → `4px`), used before the multiplier
+    #[serde(default)]
+    pub scale: HashMap<String, String>,
+}
+
+fn default_spacing_multiplier() -> f64 {
+    0.25
+}
+
+fn default_spacing_unit() -> String {
+    "rem".to_string()
+}
+
+impl Default for Spacing {
+    fn default() -> Self {
+        Self {
+            multiplier: default_spacing_multiplier(),
+            unit: default_spacing_unit(),
+            scale: HashMap::new(),
+        }
+    }
+}
+
+/// How collected CSS is handed to the bundler
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CssOutput {
+    /// Leading `/* @silk-css {...} */` comment only
+    #[default]
+    Comment,
+
+    /// Leading comment plus a side-effect `import "virtual:silk/<hash>.css"`
+    /// so CSS is code-split with the module that uses it
+    Import,
+}
+
+fn default_import_pattern() -> String {
+    "virtual:silk/[hash].css".to_string()
+}
+
+fn default_import_sources() -> Vec<String> {
+    vec!["@sylphx/silk".to_string()]
+}
+
+fn default_functions() -> Vec<String> {
+    vec!["css".to_string()]
 }
 
-fn default_prefix() -> String {
-    "silk".to_string()
+fn default_exclude() -> Vec<String> {
+    vec!["**/node_modules/**".to_string()]
+}
+
+/// Default breakpoint configuration (matches Babel's `DEFAULT_BREAKPOINTS`)
+fn default_breakpoints() -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    map.insert("sm".to_string(), "640px".to_string());
+    map.insert("md".to_string(), "768px".to_string());
+    map.insert("lg".to_string(), "1024px".to_string());
+    map.insert("xl".to_string(), "1280px".to_string());
+    map.insert("2xl".to_string(), "1536px".to_string());
+    map
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             production: false,
-            class_prefix: default_prefix(),
+            class_prefix: None,
+            class_naming: ClassNaming::default(),
+            class_name_strategy: None,
+            class_name_template: None,
+            hash_salt: String::new(),
+            dedupe_by_declaration: false,
+            css_output: CssOutput::default(),
+            import_pattern: default_import_pattern(),
+            import_sources: default_import_sources(),
+            functions: default_functions(),
+            breakpoints: default_breakpoints(),
+            tokens: HashMap::new(),
+            token_output: TokenOutput::default(),
+            spacing: None,
+            strict: false,
+            include: Vec::new(),
+            exclude: default_exclude(),
         }
     }
 }
 
+/// JSON keys accepted by [`Config`]
+const CONFIG_FIELDS: &[&str] = &[
+    "production",
+    "classPrefix",
+    "classNaming",
+    "classNameStrategy",
+    "classNameTemplate",
+    "hashSalt",
+    "dedupeByDeclaration",
+    "cssOutput",
+    "importPattern",
+    "importSources",
+    "functions",
+    "breakpoints",
+    "tokens",
+    "tokenOutput",
+    "spacing",
+    "strict",
+    "include",
+    "exclude",
+];
+
+/// Parse the plugin's JSON config.
+///
+/// Unlike a plain `serde_json::from_str`, this reports every unknown or mistyped
+/// field (with the closest valid key for typos) instead of stopping at the first one.
+pub fn parse_config(json: &str) -> Result<Config, Vec<Diagnostic>> {
+    let invalid = |message: String| Diagnostic::error(DiagnosticCode::InvalidConfig, DUMMY_SP, message);
+
+    let fields = match serde_json::from_str::<serde_json::Value>(json) {
+        Ok(serde_json::Value::Object(fields)) => fields,
+        Ok(_) => return Err(vec![invalid("plugin config must be a JSON object".to_string())]),
+        Err(err) => return Err(vec![invalid(format!("plugin config is not valid JSON: {}", err))]),
+    };
+
+    let mut errors = Vec::new();
+    for (key, value) in &fields {
+        if !CONFIG_FIELDS.contains(&key.as_str()) {
+            let message = match closest_config_field(key) {
+                Some(suggestion) => format!("unknown config field `{}`, did you mean `{}`?", key, suggestion),
+                None => format!("unknown config field `{}`, expected one of `{}`", key, CONFIG_FIELDS.join("`, `")),
+            };
+            errors.push(invalid(message));
+            continue;
+        }
+
+        // Check each field on its own so every mistyped value is reported
+        let single = serde_json::Value::Object([(key.clone(), value.clone())].into_iter().collect());
+        if let Err(err) = serde_json::from_value::<Config>(single) {
+            errors.push(invalid(format!("invalid value for config field `{}`: {}", key, err)));
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let config: Config = serde_json::from_value(serde_json::Value::Object(fields))
+        .map_err(|err| vec![invalid(format!("invalid plugin config: {}", err))])?;
+
+    if let Some(template) = &config.class_name_template {
+        if let Err(reason) = validate_class_name_template(template) {
+            errors.push(invalid(format!("invalid classNameTemplate `{}`: {}", template, reason)));
+        }
+    }
+
+    if !config.hash_salt.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        errors.push(invalid(format!(
+            "invalid hashSalt `{}`: only letters, digits, `-` and `_` are allowed",
+            config.hash_salt
+        )));
+    }
+
+    for (name, width) in &config.breakpoints {
+        if let Err(reason) = validate_css_value(name).and(validate_css_value(width)) {
+            errors.push(invalid(format!("invalid breakpoint `{}`: {}", name, reason)));
+        }
+    }
+
+    errors.extend(tokens::validate_tokens(&config.tokens).into_iter().map(invalid));
+
+    if let Some(spacing) = &config.spacing {
+        if !spacing.multiplier.is_finite() {
+            errors.push(invalid("invalid spacing multiplier: must be a finite number".to_string()));
+        }
+        if !spacing.unit.chars().all(|c| c.is_ascii_alphabetic() || c == '%') {
+            errors.push(invalid(format!("invalid spacing unit `{}`: only letters and `%` are allowed", spacing.unit)));
+        }
+        let mut steps: Vec<_> = spacing.scale.iter().collect();
+        steps.sort();
+        for (name, value) in steps {
+            if let Err(reason) = validate_css_value(value) {
+                errors.push(invalid(format!("invalid spacing step `{}`: {}", name, reason)));
+            }
+        }
+    }
+
+    for pattern in config.include.iter().chain(&config.exclude) {
+        if let Err(err) = glob::Pattern::new(pattern) {
+            errors.push(invalid(format!("invalid glob pattern `{}`: {}", pattern, err)));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(config)
+    } else {
+        Err(errors)
+    }
+}
+
+impl Config {
+    /// `classPrefix`, or `silk` when it is unset
+    pub fn class_prefix(&self) -> &str {
+        self.class_prefix.as_deref().unwrap_or("silk")
+    }
+
+    /// Naming strategy in effect, defaulting from `production`
+    pub fn strategy(&self) -> ClassNameStrategy {
+        match self.class_name_strategy {
+            Some(strategy) => strategy,
+            None if self.production => ClassNameStrategy::Hash,
+            None => ClassNameStrategy::Readable,
+        }
+    }
+
+    /// Whether class names depend on the position of a declaration in its module
+    /// (`sequential-minimal` or an `[index]` placeholder)
+    pub fn uses_sequence(&self) -> bool {
+        self.strategy() == ClassNameStrategy::SequentialMinimal
+            || self.class_name_template.as_ref().is_some_and(|template| template.contains("[index]"))
+    }
+
+    /// Check `include`/`exclude` against a file.
+    ///
+    /// Patterns match either the filename as given by the host or, when `cwd` is
+    /// known, the path relative to it (`src/**/*.tsx`). Without a filename the
+    /// filters cannot apply and every file is transformed.
+    pub fn matches_file(&self, filename: &str, cwd: Option<&str>) -> bool {
+        if filename.is_empty() {
+            return true;
+        }
+
+        (self.include.is_empty() || matches_any(&self.include, filename, cwd))
+            && !matches_any(&self.exclude, filename, cwd)
+    }
+
+    /// Warning for a file that `include` skips only because the host passed its
+    /// basename (Turbopack), so patterns with directories like `src/**/*.tsx` cannot match
+    pub fn include_warning(&self, filename: &str, cwd: Option<&str>) -> Option<Diagnostic> {
+        let basename_only = !filename.is_empty() && !filename.contains(['/', '\\']);
+        let has_directories = self.include.iter().any(|pattern| pattern.contains('/'));
+        if !basename_only || !has_directories || matches_any(&self.include, filename, cwd) {
+            return None;
+        }
+
+        Some(Diagnostic::warning(
+            DiagnosticCode::InvalidConfig,
+            DUMMY_SP,
+            format!(
+                "`include` cannot be checked against `{}`: the host only passed the file's basename, \
+                 so it is skipped and its css() calls run at runtime; use basename patterns like `*.tsx`",
+                filename
+            ),
+        ))
+    }
+}
+
+/// Check glob patterns against the filename as given or its path relative to `cwd`
+fn matches_any(patterns: &[String], filename: &str, cwd: Option<&str>) -> bool {
+    let filename = filename.replace('\\', "/");
+    let relative = relative_path(&filename, cwd);
+
+    let options = glob::MatchOptions {
+        require_literal_separator: true,
+        ..Default::default()
+    };
+    patterns.iter().filter_map(|pattern| glob::Pattern::new(pattern).ok()).any(|pattern| {
+        pattern.matches_with(&filename, options)
+            || relative.as_deref().is_some_and(|relative| pattern.matches_with(relative, options))
+    })
+}
+
+/// Path of `filename` relative to `cwd`, with `/` separators, if it is inside `cwd`
+pub fn relative_path(filename: &str, cwd: Option<&str>) -> Option<String> {
+    let cwd = format!("{}/", cwd?.replace('\\', "/").trim_end_matches('/'));
+    filename.replace('\\', "/").strip_prefix(&cwd).map(str::to_string)
+}
+
+/// Cheap check for an import from `config.import_sources`, so modules that
+/// cannot contain Silk calls skip the full transform
+pub fn imports_silk(program: &Program, config: &Config) -> bool {
+    let Program::Module(module) = program else {
+        return false;
+    };
+
+    module.body.iter().any(|item| {
+        matches!(
+            item,
+            ModuleItem::ModuleDecl(ModuleDecl::Import(import))
+                if !import.type_only
+                    && config.import_sources.iter().any(|source| import.src.value.as_str() == Some(source.as_str()))
+        )
+    })
+}
+
+/// Closest valid config key to a misspelled one (`classprefix` → `classPrefix`)
+fn closest_config_field(key: &str) -> Option<&'static str> {
+    let key = key.to_lowercase().replace(['_', '-'], "");
+
+    CONFIG_FIELDS
+        .iter()
+        .map(|field| (*field, edit_distance(&key, &field.to_lowercase())))
+        .filter(|(field, distance)| *distance <= (field.len() / 3).max(2))
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(field, _)| field)
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+
+    row[b.len()]
+}
+
 /// CSS property shorthand mappings
 fn get_property_map() -> HashMap<&'static str, &'static str> {
     let mut map = HashMap::new();
@@ -64,6 +512,28 @@ fn get_property_map() -> HashMap<&'static str, &'static str> {
     map
 }
 
+/// Pseudo-selector shorthand mappings
+fn get_pseudo_map() -> HashMap<&'static str, &'static str> {
+    let mut map = HashMap::new();
+    map.insert("_hover", ":hover");
+    map.insert("_focus", ":focus");
+    map.insert("_active", ":active");
+    map.insert("_disabled", ":disabled");
+    map.insert("_visited", ":visited");
+    map.insert("_focusVisible", ":focus-visible");
+    map.insert("_focusWithin", ":focus-within");
+    map.insert("_checked", ":checked");
+    map.insert("_before", "::before");
+    map.insert("_after", "::after");
+    map.insert("_placeholder", "::placeholder");
+    map.insert("_selection", "::selection");
+    map.insert("_first", ":first-child");
+    map.insert("_last", ":last-child");
+    map.insert("_odd", ":nth-child(odd)");
+    map.insert("_even", ":nth-child(even)");
+    map
+}
+
 /// Convert camelCase to kebab-case
 fn camel_to_kebab(s: &str) -> String {
     let mut result = String::new();
@@ -86,13 +556,59 @@ pub fn resolve_css_property(property: &str) -> String {
         .unwrap_or_else(|| camel_to_kebab(property))
 }
 
-/// Normalize CSS value (add units)
+/// Resolve CSS pseudo-selector from shorthand (`_hover` → `:hover`)
+/// Unknown shorthands fall back to a kebab-cased pseudo-class (`_firstOfType` → `:first-of-type`)
+pub fn resolve_pseudo_selector(pseudo: &str) -> String {
+    let map = get_pseudo_map();
+    map.get(pseudo)
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| format!(":{}", camel_to_kebab(pseudo.trim_start_matches('_'))))
+}
+
+/// Check if a property key is a pseudo-selector shorthand
+pub fn is_pseudo_selector(property: &str) -> bool {
+    property.starts_with('_')
+}
+
+/// Normalize CSS value (add units) without a `spacing` config
 pub fn normalize_css_value(property: &str, value: &str) -> String {
+    normalize_spaced_css_value(property, value, None)
+}
+
+/// Check if a property takes spacing values (margin, padding, gap, inset, top/right/bottom/left)
+pub fn is_spacing_property(property: &str) -> bool {
+    let css_property = resolve_css_property(property);
+    css_property.starts_with("margin")
+        || css_property.starts_with("padding")
+        || css_property == "gap"
+        || css_property.ends_with("-gap")
+        || is_position_property(property)
+}
+
+/// Check if a property is an offset (inset, top/right/bottom/left)
+fn is_position_property(property: &str) -> bool {
+    let css_property = resolve_css_property(property);
+    css_property.starts_with("inset") || matches!(css_property.as_str(), "top" | "right" | "bottom" | "left")
+}
+
+/// Normalize CSS value (add units), resolving spacing properties through `spacing`.
+/// Without one, numbers keep the Babel plugin's units: `0.25rem` steps for properties
+/// written with a leading `p` or `m` (including `maxW` and `perspective`) and for `gap`.
+pub fn normalize_spaced_css_value(property: &str, value: &str, spacing: Option<&Spacing>) -> String {
+    let default_spacing = Spacing::default();
+    let is_spacing = match spacing {
+        Some(_) => is_spacing_property(property),
+        None => property.starts_with('p') || property.starts_with('m') || property == "gap",
+    };
+    let spacing = spacing.unwrap_or(&default_spacing);
+    if let Some(step) = spacing.scale.get(value).filter(|_| is_spacing) {
+        return step.clone();
+    }
+
     // Try to parse as number
     if let Ok(num) = value.parse::<f64>() {
-        // Spacing properties use 0.25rem units
-        if property.starts_with('p') || property.starts_with('m') || property == "gap" {
-            return format!("{}rem", num * 0.25);
+        if is_spacing {
+            return format!("{}{}", num * spacing.multiplier, spacing.unit);
         }
 
         // Unitless properties
@@ -124,11 +640,14 @@ pub fn normalize_css_value(property: &str, value: &str) -> String {
 /// }
 /// return (h >>> 0).toString(36)
 /// ```
+///
+/// `charCodeAt` reads UTF-16 code units, so characters outside the BMP (emoji)
+/// are hashed as their two surrogates, not as one code point.
 fn murmur_hash2(s: &str) -> String {
     let mut h: u32 = 0;
 
-    for ch in s.chars() {
-        let c = ch as u32;
+    for unit in s.encode_utf16() {
+        let c = unit as u32;
         // IMPORTANT: Must XOR first, THEN multiply (not separate steps)
         h = (h ^ c).wrapping_mul(0x5bd1e995);
         h ^= h >> 13;
@@ -156,190 +675,1403 @@ fn base36_encode(mut num: u32) -> String {
     result.into_iter().collect()
 }
 
-/// Hash property-value pair (matches Babel plugin format)
-fn hash_property_value(property: &str, value: &str, variant: &str) -> String {
+/// Hash property-value pair (matches Babel plugin format when `salt` is empty)
+fn hash_property_value(property: &str, value: &str, variant: &str, salt: &str) -> String {
     // Format: "property:value:variant" (same as Babel plugin)
     let content = if variant.is_empty() {
         format!("{}:\"{}\":", property, value)
     } else {
         format!("{}:\"{}\":{}", property, value, variant)
     };
-    murmur_hash2(&content)
+    murmur_hash2(&salted(&content, salt))
+}
+
+/// Prefix hash input with `hashSalt` (unchanged without one)
+fn salted(content: &str, salt: &str) -> String {
+    if salt.is_empty() {
+        content.to_string()
+    } else {
+        format!("{}|{}", salt, content)
+    }
 }
 
 /// Generate class name for property-value pair
 /// Supports both development and production modes
 pub fn generate_class_name(property: &str, value: &str, config: &Config) -> String {
+    generate_variant_class_name(property, value, "", config)
+}
+
+/// Generate class name for a property-value pair under a variant
+/// (e.g. `hover` for `_hover`), which gets its own hash
+pub fn generate_variant_class_name(
+    property: &str,
+    value: &str,
+    variant: &str,
+    config: &Config,
+) -> String {
+    generate_sequenced_class_name(property, value, variant, None, config)
+}
+
+/// Position of a declaration among the distinct declarations of its module,
+/// used by `sequential-minimal` names and the `[index]` placeholder
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Sequence<'a> {
+    /// Short id of the module, so sequential names from different modules never clash
+    pub module: &'a str,
+
+    /// 0-based index of the declaration in the module
+    pub index: usize,
+}
+
+/// Generate class name for a declaration with any strategy or `classNameTemplate`.
+/// Without a `sequence` the declaration's module is unknown, so `sequential-minimal`
+/// falls back to hashed names and `[index]` to `[hash]`.
+pub fn generate_sequenced_class_name(
+    property: &str,
+    value: &str,
+    variant: &str,
+    sequence: Option<Sequence>,
+    config: &Config,
+) -> String {
     // IMPORTANT: Hash using ORIGINAL property and value (not resolved/normalized)
-    // This matches Babel plugin behavior
-    let hash = hash_property_value(property, value, "");
+    // This matches Babel plugin behavior, unless `dedupeByDeclaration` is set
+    let (property, value) = naming_declaration(property, value, config);
+    let (property, value) = (property.as_ref(), value.as_ref());
+    let hash = match config.class_naming {
+        ClassNaming::Compact => hash_property_value(property, value, variant, &config.hash_salt),
+        // Babel hashes `${property}-${value}${variant}`
+        ClassNaming::Babel => murmur_hash2(&salted(&format!("{}-{}{}", property, value, variant), &config.hash_salt)),
+    };
 
-    if config.production {
-        // Production mode: short hash (6-7 chars) with digit mapping
-        // CSS class names cannot start with a digit, so map 0-9 to g-p
-        let mut short_hash = hash.chars().take(8).collect::<String>();
+    if let Some(template) = &config.class_name_template {
+        return expand_class_name_template(template, |name, arg| {
+            Ok(match (name, arg) {
+                ("prefix", None) => config.class_prefix().to_string(),
+                ("prop", None) => property.to_string(),
+                ("value", None) => readable_value(value, config.class_naming),
+                ("variant", None) => variant.to_string(),
+                ("hash", None) => hash.clone(),
+                ("hash", Some(len)) => hash.chars().take(len.parse().unwrap_or(usize::MAX)).collect(),
+                ("index", None) => match sequence {
+                    Some(sequence) => format!("{}{}", sequence.module, minimal_ident(sequence.index)),
+                    None => hash.clone(),
+                },
+                _ => String::new(),
+            })
+        })
+        .unwrap_or_default();
+    }
 
-        if let Some(first_char) = short_hash.chars().next() {
-            if first_char.is_ascii_digit() {
-                // Map 0→g, 1→h, 2→i, ..., 9→p
-                let mapped_char = (b'g' + (first_char as u8 - b'0')) as char;
-                short_hash = format!("{}{}", mapped_char, &short_hash[1..]);
+    let strategy = match (config.strategy(), sequence) {
+        (ClassNameStrategy::SequentialMinimal, None) => ClassNameStrategy::Hash,
+        (strategy, _) => strategy,
+    };
+    match (strategy, config.class_naming) {
+        (ClassNameStrategy::SequentialMinimal, _) => {
+            let sequence = sequence.unwrap_or_default();
+            let name = format!("{}{}", sequence.module, minimal_ident(sequence.index));
+
+            // Only a `classPrefix` that is set is added; the default would double their length
+            match config.class_prefix.as_deref() {
+                Some(prefix) if prefix != "s" => format!("{}{}", prefix, name),
+                _ => name,
+            }
+        }
+        (ClassNameStrategy::Hash, ClassNaming::Compact) => {
+            // Production mode: short hash (6-7 chars) with digit mapping
+            // CSS class names cannot start with a digit, so map 0-9 to g-p
+            let short_hash = map_leading_digit(&hash.chars().take(8).collect::<String>());
+
+            // Apply custom prefix if provided (for branding)
+            let prefix = config.class_prefix();
+            if !prefix.is_empty() && prefix != "s" {
+                return format!("{}{}", prefix, short_hash);
+            }
+
+            short_hash
+        }
+        // Babel: `{classPrefix || "s"}{hash}`
+        (ClassNameStrategy::Hash, ClassNaming::Babel) => {
+            let prefix = config.class_prefix.as_deref().filter(|prefix| !prefix.is_empty()).unwrap_or("s");
+            format!("{}{}", prefix, hash)
+        }
+        (ClassNameStrategy::Readable, naming) => {
+            // Development mode: descriptive class names
+            // Babel: `classPrefix ?? "silk"`, keeping an empty prefix; compact names fall back to `silk`
+            let mut prefix = match naming {
+                ClassNaming::Compact if config.class_prefix().is_empty() => "silk",
+                _ => config.class_prefix(),
+            }
+            .to_string();
+            if !config.hash_salt.is_empty() {
+                prefix = format!("{}_{}", prefix, config.hash_salt);
+            }
+            let safe_value = readable_value(value, naming);
+            // Babel keeps 4 characters; compact names keep the whole hash, since the
+            // readable part is truncated and cannot tell declarations apart
+            let short_hash = match naming {
+                ClassNaming::Compact => hash.clone(),
+                ClassNaming::Babel => hash.chars().take(4).collect::<String>(),
+            };
+
+            if variant.is_empty() {
+                format!("{}_{}_{}_{}", prefix, property, safe_value, short_hash)
+            } else {
+                format!("{}_{}_{}_{}_{}", prefix, variant, property, safe_value, short_hash)
+            }
+        }
+    }
+}
+
+/// Property and value a class is named after: as written, or as emitted with `dedupeByDeclaration`
+fn naming_declaration<'a>(property: &'a str, value: &'a str, config: &Config) -> (Cow<'a, str>, Cow<'a, str>) {
+    if config.dedupe_by_declaration {
+        (
+            Cow::Owned(resolve_css_property(property)),
+            Cow::Owned(normalize_spaced_css_value(property, value, config.spacing.as_ref())),
+        )
+    } else {
+        (Cow::Borrowed(property), Cow::Borrowed(value))
+    }
+}
+
+/// Shorten a value for readable class names
+fn readable_value(value: &str, naming: ClassNaming) -> String {
+    match naming {
+        // A leading minus is dropped like Babel does (the hash keeps `-4` distinct from `4`)
+        ClassNaming::Compact => value
+            .trim_start_matches('-')
+            .replace([' ', '.'], "_")
+            .replace(['(', ')', '#'], "")
+            .chars()
+            .take(10)
+            .collect(),
+        // Babel keeps ASCII letters and digits only, lowercased (`val` if nothing is left)
+        ClassNaming::Babel => {
+            let safe_value = value
+                .chars()
+                .filter(char::is_ascii_alphanumeric)
+                .map(|c| c.to_ascii_lowercase())
+                .take(10)
+                .collect::<String>();
+            if safe_value.is_empty() {
+                "val".to_string()
+            } else {
+                safe_value
+            }
+        }
+    }
+}
+
+/// Map a leading digit to a letter (0→g, 1→h, ..., 9→p), since class selectors cannot start with one
+fn map_leading_digit(s: &str) -> String {
+    match s.chars().next() {
+        Some(first_char) if first_char.is_ascii_digit() => {
+            let mapped_char = (b'g' + (first_char as u8 - b'0')) as char;
+            format!("{}{}", mapped_char, &s[1..])
+        }
+        _ => s.to_string(),
+    }
+}
+
+/// Shortest identifier for a sequence index: `a`–`z`, then a letter followed by base-36 digits
+fn minimal_ident(index: usize) -> String {
+    let letter = (b'a' + (index % 26) as u8) as char;
+    match index / 26 {
+        0 => letter.to_string(),
+        rest => format!("{}{}", letter, base36_encode(rest as u32)),
+    }
+}
+
+/// Id for sequential names of a module, derived from its path and `hashSalt`.
+/// The whole hash is kept and padded to 7 characters, so different paths only share
+/// an id on a full hash collision and no id is a prefix of another.
+pub fn module_id(path: &str, salt: &str) -> String {
+    map_leading_digit(&format!("{:0>7}", murmur_hash2(&salted(path, salt))))
+}
+
+/// Name for a declaration whose class is taken by a different declaration.
+/// The declaration is hashed after the taken name: murmur's state only depends on what it
+/// has read so far, so colliding inputs would still collide with a suffix appended.
+fn fallback_class_name(class_name: &str, declaration: &str) -> String {
+    format!("{}_{}", class_name, murmur_hash2(&format!("{}|{}", class_name, declaration)))
+}
+
+/// Expand `[name]` and `[name:arg]` placeholders of a class name template.
+/// A placeholder that expands to nothing also drops a `-` or `_` right after it,
+/// so `[variant]-[prop]` gives `bg` rather than `-bg` for base styles.
+fn expand_class_name_template(
+    template: &str,
+    mut placeholder: impl FnMut(&str, Option<&str>) -> Result<String, String>,
+) -> Result<String, String> {
+    let mut expanded = String::new();
+    let mut rest = template;
+
+    while let Some(open) = rest.find('[') {
+        expanded.push_str(&rest[..open]);
+        let close = rest[open..]
+            .find(']')
+            .map(|close| open + close)
+            .ok_or_else(|| "unclosed `[`".to_string())?;
+
+        let (name, arg) = match rest[open + 1..close].split_once(':') {
+            Some((name, arg)) => (name, Some(arg)),
+            None => (&rest[open + 1..close], None),
+        };
+        let value = placeholder(name, arg)?;
+
+        rest = &rest[close + 1..];
+        if value.is_empty() {
+            rest = rest.strip_prefix(['-', '_']).unwrap_or(rest);
+        }
+        expanded.push_str(&value);
+    }
+
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Check a `classNameTemplate`: placeholders must be known, and `[hash]`, `[hash:N]` or
+/// `[index]` must keep different declarations apart
+fn validate_class_name_template(template: &str) -> Result<(), String> {
+    let mut unique = false;
+
+    expand_class_name_template(template, |name, arg| {
+        match (name, arg) {
+            ("prefix" | "prop" | "value" | "variant", None) => {}
+            ("hash", None) | ("index", None) => unique = true,
+            ("hash", Some(len)) => match len.parse::<usize>() {
+                Ok(len) if len > 0 => unique = true,
+                _ => return Err(format!("`[hash:{}]` needs a positive length", len)),
+            },
+            (name, Some(arg)) => return Err(format!("unknown placeholder `[{}:{}]`", name, arg)),
+            (name, None) => return Err(format!("unknown placeholder `[{}]`", name)),
+        }
+        Ok("x".to_string())
+    })?;
+
+    if !unique {
+        return Err("needs `[hash]`, `[hash:N]` or `[index]` to tell declarations apart".to_string());
+    }
+
+    let sample = expand_class_name_template(template, |_, _| Ok("x".to_string()))?;
+    if !sample.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err("only letters, digits, `-` and `_` are allowed outside placeholders".to_string());
+    }
+
+    Ok(())
+}
+
+/// Generate CSS rule for property-value pair
+pub fn generate_css_rule(class_name: &str, property: &str, value: &str) -> String {
+    generate_pseudo_css_rule(class_name, "", property, value, None)
+}
+
+/// Generate CSS rule with a pseudo-selector shorthand (`_hover`) appended to the class selector
+pub fn generate_pseudo_css_rule(
+    class_name: &str,
+    pseudo: &str,
+    property: &str,
+    value: &str,
+    spacing: Option<&Spacing>,
+) -> String {
+    let css_property = resolve_css_property(property);
+    let css_value = normalize_spaced_css_value(property, value, spacing);
+    let selector = if pseudo.is_empty() {
+        String::new()
+    } else {
+        resolve_pseudo_selector(pseudo)
+    };
+
+    format!(".{}{} {{ {}: {}; }}", css_escape(class_name), selector, css_property, css_value)
+}
+
+/// Escape a class name for use in a selector, per the CSSOM `CSS.escape()` algorithm.
+/// Dev class names may contain characters like `%` or `/` that are only valid escaped.
+pub fn css_escape(ident: &str) -> String {
+    let chars: Vec<char> = ident.chars().collect();
+    let mut escaped = String::with_capacity(ident.len());
+
+    for (i, &c) in chars.iter().enumerate() {
+        match c {
+            '\0' => escaped.push('\u{fffd}'),
+            // Control characters, and digits where an identifier cannot start with one
+            '\u{1}'..='\u{1f}' | '\u{7f}' => escaped.push_str(&format!("\\{:x} ", c as u32)),
+            '0'..='9' if i == 0 || (i == 1 && chars[0] == '-') => {
+                escaped.push_str(&format!("\\{:x} ", c as u32))
+            }
+            '-' if i == 0 && chars.len() == 1 => escaped.push_str("\\-"),
+            c if c >= '\u{80}' || c == '-' || c == '_' || c.is_ascii_alphanumeric() => escaped.push(c),
+            c => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+        }
+    }
+
+    escaped
+}
+
+/// Prefix a rule with the source location that produced it
+/// (`/* src/components/Button.tsx:42:10 */ .a { ... }`)
+pub fn annotate_css_rule(rule: &str, location: &str) -> String {
+    format!("/* {} */ {}", location.replace("*/", "* /"), rule)
+}
+
+/// Wrap a rule in a `min-width` media query for a breakpoint.
+/// `base` needs no media query; unknown breakpoints are used as the width itself.
+pub fn wrap_breakpoint(rule: String, breakpoint: &str, config: &Config) -> String {
+    if breakpoint == "base" {
+        return rule;
+    }
+
+    let min_width = config
+        .breakpoints
+        .get(breakpoint)
+        .map(String::as_str)
+        .unwrap_or(breakpoint);
+    format!("@media (min-width: {}) {{ {} }}", min_width, rule)
+}
+
+/// Sort key that puts rules for narrower breakpoints first: `0` for base rules,
+/// the min-width in px for media rules (`em`/`rem` count as 16px), and infinity for
+/// widths that cannot be compared, which keep their source order at the end
+fn breakpoint_order(breakpoint: Option<&str>, config: &Config) -> f64 {
+    let Some(breakpoint) = breakpoint.filter(|&breakpoint| breakpoint != "base") else {
+        return 0.0;
+    };
+
+    let min_width = config.breakpoints.get(breakpoint).map(String::as_str).unwrap_or(breakpoint);
+    min_width_order(min_width)
+}
+
+/// Sort key of a `min-width` value in px, see [`breakpoint_order`]
+fn min_width_order(min_width: &str) -> f64 {
+    let min_width = min_width.trim();
+    let split = min_width
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(min_width.len());
+    let (number, unit) = min_width.split_at(split);
+    match (number.parse::<f64>(), unit) {
+        (Ok(width), "px" | "") => width,
+        (Ok(width), "em" | "rem") => width * 16.0,
+        _ => f64::INFINITY,
+    }
+}
+
+/// Sort key of a collected rule, read back from its `@media (min-width: ...)` wrapper
+fn css_rule_order(rule: &str) -> f64 {
+    match strip_css_annotation(rule).strip_prefix("@media (min-width: ") {
+        Some(rest) => min_width_order(rest.split(')').next().unwrap_or_default()),
+        None => 0.0,
+    }
+}
+
+/// A rule without the source location [`annotate_css_rule`] put in front of it
+fn strip_css_annotation(rule: &str) -> &str {
+    rule.strip_prefix("/* ")
+        .and_then(|rest| rest.split_once(" */ "))
+        .map_or(rule, |(_, rule)| rule)
+}
+
+/// A static style declaration extracted from a css() object
+#[derive(Debug, Clone, PartialEq)]
+pub struct Style {
+    /// Property as written (e.g. `bg`)
+    pub property: String,
+
+    /// Value as written, before unit normalization
+    pub value: String,
+
+    /// Pseudo-selector shorthand this declaration is nested under (e.g. `_hover`)
+    pub pseudo: Option<String>,
+
+    /// Breakpoint of a responsive value (e.g. `md`, or `base`)
+    pub breakpoint: Option<String>,
+
+    /// Span of the property this declaration came from
+    pub span: Span,
+}
+
+impl Style {
+    /// Variant segment used for hashing and dev class names
+    /// (`_hover` → `hover`, `md` → `md`, both → `hover_md`)
+    pub fn variant(&self) -> String {
+        let pseudo = self.pseudo.as_deref().map(|pseudo| pseudo.trim_start_matches('_'));
+
+        match (pseudo, self.breakpoint.as_deref()) {
+            (Some(pseudo), Some(breakpoint)) => format!("{}_{}", pseudo, breakpoint),
+            (Some(variant), None) | (None, Some(variant)) => variant.to_string(),
+            (None, None) => String::new(),
+        }
+    }
+
+    /// Generate the class name for this declaration
+    pub fn class_name(&self, config: &Config) -> String {
+        generate_variant_class_name(&self.property, &self.value, &self.variant(), config)
+    }
+
+    /// Generate the class name for this declaration at a position in its module
+    pub fn sequenced_class_name(&self, sequence: Option<Sequence>, config: &Config) -> String {
+        generate_sequenced_class_name(&self.property, &self.value, &self.variant(), sequence, config)
+    }
+
+    /// Generate the CSS rule for this declaration
+    pub fn css_rule(&self, class_name: &str, config: &Config) -> String {
+        let rule = generate_pseudo_css_rule(
+            class_name,
+            self.pseudo.as_deref().unwrap_or(""),
+            &self.property,
+            &self.value,
+            config.spacing.as_ref(),
+        );
+
+        match &self.breakpoint {
+            Some(breakpoint) => wrap_breakpoint(rule, breakpoint, config),
+            None => rule,
+        }
+    }
+}
+
+/// Styles extracted from a css() object argument
+#[derive(Debug, Clone, Default)]
+pub struct ExtractedStyles {
+    /// Static styles that can be compiled at build-time
+    pub styles: Vec<Style>,
+
+    /// Properties that contain dynamic values and must remain at runtime
+    pub dynamic_props: Vec<PropOrSpread>,
+
+    /// Why each of `dynamic_props` could not be compiled
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Extract style properties from ObjectExpression
+/// Dynamic properties are skipped; use [`extract_static_styles`] to keep them
+pub fn extract_styles(obj: &ObjectLit) -> Vec<Style> {
+    extract_static_styles(obj, &ModuleConstants::default(), &HashMap::new())
+        .map(|extracted| extracted.styles)
+        .unwrap_or_default()
+}
+
+/// Split a css() object into static styles and dynamic properties
+/// Returns an error if the object cannot be compiled at all (e.g. a spread that is not a module constant).
+/// Properties that refer to a token missing from `tokens` are left dynamic as well.
+pub fn extract_static_styles(
+    obj: &ObjectLit,
+    constants: &ModuleConstants,
+    tokens: &HashMap<String, Token>,
+) -> Result<ExtractedStyles, Diagnostic> {
+    let mut extracted = ExtractedStyles::default();
+
+    for prop in inline_spreads(obj, constants, 0)? {
+        match extract_property(&prop, None, constants).and_then(|styles| check_tokens(styles, tokens)) {
+            Ok(styles) => extracted.styles.extend(styles),
+            Err(diagnostic) => {
+                extracted.diagnostics.push(diagnostic);
+                extracted.dynamic_props.push(PropOrSpread::Prop(Box::new(prop)));
+            }
+        }
+    }
+
+    Ok(extracted)
+}
+
+/// Reject a property whose styles refer to unknown tokens: emitting the reference
+/// would produce invalid CSS, while the css() runtime may still resolve it
+fn check_tokens(styles: Vec<Style>, tokens: &HashMap<String, Token>) -> Result<Vec<Style>, Diagnostic> {
+    for style in &styles {
+        if let Some(reason) = tokens::unknown_token(&style.value, tokens) {
+            return Err(Diagnostic::warning(
+                DiagnosticCode::UnknownToken,
+                style.span,
+                format!("{}; `{}` is left to the css() runtime", reason, style.property),
+            ));
+        }
+    }
+    Ok(styles)
+}
+
+/// Maximum depth of nested spreads (`const a = { ...b }`) to inline
+const MAX_SPREAD_DEPTH: usize = 8;
+
+/// Inline spreads of module-level `const` objects (`{ ...baseCard, p: 4 }`) and merge
+/// duplicate keys with last-wins semantics.
+/// Returns an error if a spread cannot be resolved to a fully static object.
+fn inline_spreads(obj: &ObjectLit, constants: &ModuleConstants, depth: usize) -> Result<Vec<Prop>, Diagnostic> {
+    let mut props = Vec::new();
+    for prop in &obj.props {
+        match prop {
+            PropOrSpread::Prop(prop) => props.push((**prop).clone()),
+            PropOrSpread::Spread(spread) => {
+                if depth >= MAX_SPREAD_DEPTH {
+                    return Err(Diagnostic::warning(
+                        DiagnosticCode::DynamicSpread,
+                        spread.span(),
+                        format!("spreads nested more than {} levels deep cannot be compiled", MAX_SPREAD_DEPTH),
+                    ));
+                }
+
+                let Expr::Object(source) = evaluate::unwrap_static_expr(&spread.expr, constants) else {
+                    return Err(Diagnostic::warning(
+                        DiagnosticCode::DynamicSpread,
+                        spread.span(),
+                        "spread is not a module-level const object; the whole css() call is left to the runtime",
+                    ));
+                };
+                let inlined = inline_spreads(source, constants, depth + 1)?;
+
+                // Spread sources must be fully static: their expressions cannot move to the call site
+                if let Some(Err(reason)) = inlined.iter().map(|prop| extract_property(prop, None, constants)).find(Result::is_err) {
+                    return Err(Diagnostic::warning(
+                        DiagnosticCode::DynamicSpread,
+                        spread.span(),
+                        format!("spread source is not fully static ({}); the whole css() call is left to the runtime", reason.message),
+                    ));
+                }
+                props.extend(inlined);
+            }
+        }
+    }
+
+    // Last one wins: drop properties overridden later in the object
+    let keys: Vec<Option<String>> = props.iter().map(|prop| prop_key(prop, constants)).collect();
+    let mut merged = Vec::with_capacity(props.len());
+    for (i, prop) in props.into_iter().enumerate() {
+        let overridden = keys[i].is_some() && keys[i + 1..].contains(&keys[i]);
+        if !overridden {
+            merged.push(prop);
+        }
+    }
+
+    Ok(merged)
+}
+
+/// Get the static key of an object property, if it has one
+fn prop_key(prop: &Prop, constants: &ModuleConstants) -> Option<String> {
+    match prop {
+        Prop::KeyValue(kv) => prop_name(&kv.key, constants).ok(),
+        Prop::Shorthand(ident) => Some(ident.sym.as_str().to_string()),
+        _ => None,
+    }
+}
+
+/// Error for a property whose value is only known at runtime
+fn dynamic_value(prop: &Prop, name: &str) -> Diagnostic {
+    Diagnostic::warning(
+        DiagnosticCode::DynamicValue,
+        prop.span(),
+        format!("dynamic value for `{}` cannot be compiled and is left to the css() runtime", name),
+    )
+}
+
+/// Error for a key or value rejected by [`validate_css_value`]
+fn invalid_value(prop: &Prop, name: &str, reason: &str) -> Diagnostic {
+    Diagnostic::error(
+        DiagnosticCode::InvalidValue,
+        prop.span(),
+        format!("`{}` cannot be compiled safely: {}", name, reason),
+    )
+}
+
+/// Check that a key is a plain identifier (`bg`, `_hover`, `--brand-color`)
+fn is_css_ident(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Check that a value stays inside its declaration: quotes, comments, parentheses and
+/// brackets are balanced, and `;`, `{` and `}` only appear inside strings or unquoted `url()`s
+pub fn validate_css_value(value: &str) -> Result<(), String> {
+    let mut closers = Vec::new();
+    let mut chars = value.chars().peekable();
+    // Identifier right before the current character, to spot `url(`
+    let mut name = String::new();
+
+    while let Some(c) = chars.next() {
+        if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+            name.push(c);
+            continue;
+        }
+        let function = std::mem::take(&mut name);
+
+        match c {
+            '\\' => {
+                let Some(_) = chars.next() else {
+                    return Err("trailing `\\` escapes the end of the declaration".to_string());
+                };
+            }
+            '"' | '\'' => loop {
+                match chars.next() {
+                    Some('\\') => {
+                        chars.next();
+                    }
+                    Some(quote) if quote == c => break,
+                    Some('\n') | None => return Err(format!("unterminated string starting with `{}`", c)),
+                    Some(_) => {}
+                }
+            },
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                loop {
+                    match chars.next() {
+                        Some('/') if previous == '*' => break,
+                        Some(c) => previous = c,
+                        None => return Err("unterminated comment".to_string()),
+                    }
+                }
+            }
+            '(' => {
+                let unquoted_url = function.eq_ignore_ascii_case("url") && consume_unquoted_url(&mut chars)?;
+                if !unquoted_url {
+                    closers.push(')');
+                }
+            }
+            '[' => closers.push(']'),
+            ')' | ']' => match closers.pop() {
+                Some(closer) if closer == c => {}
+                _ => return Err(format!("unbalanced `{}`", c)),
+            },
+            ';' | '{' | '}' => return Err(format!("`{}` outside a string", c)),
+            _ => {}
+        }
+    }
+
+    match closers.pop() {
+        Some(closer) => Err(format!("missing `{}`", closer)),
+        None => Ok(()),
+    }
+}
+
+/// Consume the body of an unquoted `url(...)` up to and including its `)`. Like the CSS
+/// tokenizer, the body is one token, so `;` or `}` in it (`data:image/png;base64,...`) is fine.
+/// Returns `false` without consuming the URL if it is quoted (`url("...")`), which is an
+/// ordinary function.
+fn consume_unquoted_url(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<bool, String> {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    if matches!(chars.peek(), Some('"' | '\'')) {
+        return Ok(false);
+    }
+
+    loop {
+        match chars.next() {
+            Some(')') => return Ok(true),
+            Some('\\') => {
+                if chars.next().is_none() {
+                    return Err("trailing `\\` escapes the end of the declaration".to_string());
+                }
+            }
+            Some(c) if c.is_whitespace() => {
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                if chars.next() != Some(')') {
+                    return Err("whitespace inside an unquoted `url()`".to_string());
+                }
+                return Ok(true);
+            }
+            Some(c @ ('"' | '\'' | '(')) => return Err(format!("`{}` inside an unquoted `url()`", c)),
+            Some(c) if c.is_control() => return Err("control character inside an unquoted `url()`".to_string()),
+            Some(_) => {}
+            None => return Err("missing `)`".to_string()),
+        }
+    }
+}
+
+/// Extract a single property, or explain which part of it is dynamic
+fn extract_property(prop: &Prop, pseudo: Option<&str>, constants: &ModuleConstants) -> Result<Vec<Style>, Diagnostic> {
+    let kv = match prop {
+        Prop::KeyValue(kv) => kv,
+        Prop::Shorthand(ident) => return Err(dynamic_value(prop, ident.sym.as_str())),
+        _ => {
+            return Err(Diagnostic::warning(
+                DiagnosticCode::DynamicValue,
+                prop.span(),
+                "methods and accessors cannot be compiled and are left to the css() runtime",
+            ))
+        }
+    };
+    let prop_name = prop_name(&kv.key, constants)?;
+    let value = evaluate::unwrap_static_expr(&kv.value, constants);
+
+    // Keys end up in selectors and declarations verbatim
+    if !is_css_ident(&prop_name) {
+        return Err(invalid_value(prop, &prop_name, "not a valid property name"));
+    }
+
+    // Pseudo-selectors: { _hover: { bg: 'red' } } (one level deep)
+    if is_pseudo_selector(&prop_name) {
+        let nested = match (pseudo, value) {
+            (None, Expr::Object(nested)) => nested,
+            (Some(outer), _) => {
+                return Err(Diagnostic::warning(
+                    DiagnosticCode::UnsupportedNesting,
+                    prop.span(),
+                    format!("`{}` cannot be nested inside `{}`", prop_name, outer),
+                ))
+            }
+            (None, _) => {
+                return Err(Diagnostic::warning(
+                    DiagnosticCode::UnsupportedNesting,
+                    prop.span(),
+                    format!("`{}` must be an object of styles", prop_name),
+                ))
+            }
+        };
+
+        let mut styles = Vec::new();
+        for nested_prop in inline_spreads(nested, constants, 0)? {
+            styles.extend(extract_property(&nested_prop, Some(&prop_name), constants)?);
+        }
+        return Ok(styles);
+    }
+
+    // Responsive values: { w: { base: '100%', md: '50%' } }
+    if let Expr::Object(responsive) = value {
+        return inline_spreads(responsive, constants, 0)?
+            .iter()
+            .map(|entry| {
+                let (breakpoint, value) = static_entry(entry, &prop_name, constants)?;
+                Ok(Style {
+                    property: prop_name.clone(),
+                    value,
+                    pseudo: pseudo.map(str::to_string),
+                    breakpoint: Some(breakpoint),
+                    span: entry.span(),
+                })
+            })
+            .collect();
+    }
+
+    let Some(value) = evaluate(value, constants) else {
+        return Err(dynamic_value(prop, &prop_name));
+    };
+    let value = value.into_style_value();
+    validate_css_value(&value).map_err(|reason| invalid_value(prop, &prop_name, &reason))?;
+
+    Ok(vec![Style {
+        property: prop_name,
+        value,
+        pseudo: pseudo.map(str::to_string),
+        breakpoint: None,
+        span: prop.span(),
+    }])
+}
+
+/// Get a property name, evaluating computed keys (`[KEY]: value`)
+fn prop_name(key: &PropName, constants: &ModuleConstants) -> Result<String, Diagnostic> {
+    let name = match key {
+        PropName::Ident(ident) => Some(ident.sym.as_str().to_string()),
+        PropName::Str(s) => Some(s.value.as_str().unwrap_or("").to_string()),
+        PropName::Num(n) => Some(evaluate::format_number(n.value)),
+        PropName::Computed(computed) => evaluate(&computed.expr, constants).map(StaticValue::into_style_value),
+        _ => None,
+    };
+
+    name.ok_or_else(|| {
+        Diagnostic::warning(
+            DiagnosticCode::DynamicKey,
+            key.span(),
+            "computed key cannot be resolved at build time and is left to the css() runtime",
+        )
+    })
+}
+
+/// Get the key and static value of a `key: value` entry of `property`'s responsive object
+fn static_entry(prop: &Prop, property: &str, constants: &ModuleConstants) -> Result<(String, String), Diagnostic> {
+    let Prop::KeyValue(kv) = prop else {
+        return Err(dynamic_value(prop, property));
+    };
+
+    let key = prop_name(&kv.key, constants)?;
+    let name = format!("{}.{}", property, key);
+    let Some(value) = evaluate(&kv.value, constants) else {
+        return Err(dynamic_value(prop, &name));
+    };
+    let value = value.into_style_value();
+
+    // Unknown breakpoint keys become the media query width
+    for part in [&key, &value] {
+        validate_css_value(part).map_err(|reason| invalid_value(prop, &name, &reason))?;
+    }
+    Ok((key, value))
+}
+
+/// CSS metadata for a transformed module (mirrors Babel's `file.metadata.silk`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SilkMetadata {
+    /// Array of [className, cssRule] tuples
+    pub css_rules: Vec<(String, String)>,
+
+    /// Array of class names
+    pub class_names: Vec<String>,
+
+    /// Plugin version
+    pub version: String,
+
+    /// Every property that produced one of `css_rules`, in source order (not serialized:
+    /// spans are only meaningful together with the module's source map, see [`Self::locate_sources`])
+    #[serde(skip)]
+    pub rule_sources: Vec<RuleSource>,
+
+    /// File that `sources` point into
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+
+    /// Line and column of every property in `rule_sources`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<SourceLocation>,
+}
+
+/// Where a class is used: one per property of every css() call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleSource {
+    /// Class the property compiled to
+    pub class_name: String,
+
+    /// Span of the property (or responsive entry)
+    pub span: Span,
+
+    /// Span of the enclosing css() call
+    pub call_span: Span,
+}
+
+/// Serialized position of a [`RuleSource`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceLocation {
+    pub class_name: String,
+
+    /// 1-based line of the property
+    pub line: usize,
+
+    /// 1-based column of the property
+    pub column: usize,
+}
+
+impl SilkMetadata {
+    /// Stylesheet for this module, one rule per line
+    pub fn css(&self) -> String {
+        self.css_rules
+            .iter()
+            .map(|(_, rule)| rule.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Resolve `rule_sources` to lines and columns in `filename`, so loaders and devtools
+    /// can map every use of a class back to its property
+    pub fn locate_sources<S: SourceMapper + ?Sized>(&mut self, filename: &str, source_map: &S) {
+        self.file = Some(filename.to_string());
+        self.sources = self
+            .rule_sources
+            .iter()
+            .filter(|source| !source.span.is_dummy())
+            .map(|source| {
+                let loc = source_map.lookup_char_pos(source.span.lo);
+                SourceLocation {
+                    class_name: source.class_name.clone(),
+                    line: loc.line,
+                    column: loc.col.0 + 1,
+                }
+            })
+            .collect();
+    }
+
+    /// Annotate each rule with `filename:line:column` of the first css() call that produced it
+    pub fn annotate_sources<S: SourceMapper + ?Sized>(&mut self, filename: &str, source_map: &S) {
+        let mut first_sources = HashMap::new();
+        for source in &self.rule_sources {
+            first_sources.entry(source.class_name.as_str()).or_insert(source);
+        }
+
+        for (class_name, rule) in &mut self.css_rules {
+            let Some(source) = first_sources.get(class_name.as_str()).filter(|source| !source.call_span.is_dummy()) else {
+                continue;
+            };
+
+            let loc = source_map.lookup_char_pos(source.call_span.lo);
+            let location = format!("{}:{}:{}", filename, loc.line, loc.col.0 + 1);
+            *rule = annotate_css_rule(rule, &location);
+        }
+    }
+
+    /// Combine the metadata of several modules into one stylesheet, keeping one rule per class
+    /// and the same order as within a module (base rules first, then by min-width).
+    ///
+    /// Class names only depend on the declaration, so a class with different rules in two
+    /// modules means two declarations hashed to the same name; each such class is reported
+    /// once as `SILK009`, and the rule seen first is kept.
+    pub fn merge<'a>(modules: impl IntoIterator<Item = &'a SilkMetadata>) -> (SilkMetadata, Vec<Diagnostic>) {
+        let mut merged = SilkMetadata {
+            version: PLUGIN_VERSION.to_string(),
+            ..Default::default()
+        };
+        let mut diagnostics = Vec::new();
+        // Class name → index into `merged.css_rules` and the module it came from
+        let mut rule_index: HashMap<String, (usize, Option<&str>)> = HashMap::new();
+        let mut collisions = HashSet::new();
+
+        for module in modules {
+            for (class_name, rule) in &module.css_rules {
+                let Some(&(i, file)) = rule_index.get(class_name) else {
+                    rule_index.insert(class_name.clone(), (merged.css_rules.len(), module.file.as_deref()));
+                    merged.css_rules.push((class_name.clone(), rule.clone()));
+                    continue;
+                };
+
+                // Dev rules are annotated with their own module's location
+                let existing = &merged.css_rules[i].1;
+                if strip_css_annotation(existing) != strip_css_annotation(rule) && collisions.insert(class_name) {
+                    diagnostics.push(Diagnostic::warning(
+                        DiagnosticCode::ClassCollision,
+                        DUMMY_SP,
+                        format!(
+                            "class `{}` is used for different declarations in {} and {}; set `hashSalt` or `classNameTemplate` to rename it",
+                            class_name,
+                            file.unwrap_or("another module"),
+                            module.file.as_deref().unwrap_or("another module"),
+                        ),
+                    ));
+                }
+            }
+        }
+
+        merged.css_rules.sort_by(|(_, a), (_, b)| css_rule_order(a).total_cmp(&css_rule_order(b)));
+        merged.class_names = merged.css_rules.iter().map(|(class_name, _)| class_name.clone()).collect();
+        (merged, diagnostics)
+    }
+
+    /// Serialize into the text of a `/* @silk-css {...} */` block comment
+    pub fn to_comment_text(&self) -> String {
+        let json = serde_json::to_string(self).unwrap_or_default();
+
+        // `*/` can only appear inside JSON strings, where `\/` is a valid escape
+        format!(" {} {} ", CSS_COMMENT_MARKER, json.replace("*/", "*\\/"))
+    }
+}
+
+/// Main transform visitor
+///
+/// Only calls bound to an import from `config.import_sources` are transformed.
+/// Bindings are compared by `Id` (symbol + `SyntaxContext`), so this relies on
+/// the resolver pass SWC runs before plugins to tell shadowing locals apart.
+pub struct SilkTransformVisitor {
+    config: Config,
+    css_rules: Vec<(String, String)>,
+    /// Every compiled property, in source order
+    rule_sources: Vec<RuleSource>,
+    /// [`breakpoint_order`] of each of `css_rules`
+    rule_order: Vec<f64>,
+    /// Class name → index into `css_rules`
+    rule_index: HashMap<String, usize>,
+    /// Distinct declarations (`property:value:variant`) → order of appearance
+    sequence: HashMap<String, usize>,
+    /// Scopes sequential class names to this module; unset when its path is unknown
+    module_id: Option<String>,
+    /// Class names already reported as used for two declarations
+    collisions: HashSet<String>,
+    /// Local bindings of Silk functions (`import { css as sx }`)
+    function_bindings: HashSet<Id>,
+    /// Local bindings of Silk namespaces (`import * as silk`)
+    namespace_bindings: HashSet<Id>,
+    /// Module-level `const` bindings available to style values
+    constants: ModuleConstants,
+    /// Problems found in css() calls, in source order
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl SilkTransformVisitor {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            css_rules: Vec::new(),
+            rule_sources: Vec::new(),
+            rule_order: Vec::new(),
+            rule_index: HashMap::new(),
+            sequence: HashMap::new(),
+            module_id: None,
+            collisions: HashSet::new(),
+            function_bindings: HashSet::new(),
+            namespace_bindings: HashSet::new(),
+            constants: ModuleConstants::default(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Scope sequential class names to a module, identified by its path relative to `cwd`.
+    ///
+    /// A basename (Turbopack only passes `page.tsx`) or an empty filename is not unique
+    /// across the build, so sequential names fall back to hashed ones with a warning.
+    pub fn with_filename(mut self, filename: &str, cwd: Option<&str>) -> Self {
+        let path = relative_path(filename, cwd).unwrap_or_else(|| filename.replace('\\', "/"));
+        if path.contains('/') {
+            self.module_id = Some(module_id(&path, &self.config.hash_salt));
+        } else if self.config.uses_sequence() {
+            self.diagnostics.push(Diagnostic::warning(
+                DiagnosticCode::InvalidConfig,
+                DUMMY_SP,
+                format!(
+                    "sequential class names need the module's path, but the host only passed `{}`; using hashed names",
+                    filename
+                ),
+            ));
+        }
+        self
+    }
+
+    /// Record bindings introduced by Silk import declarations
+    fn collect_imports(&mut self, module: &Module) {
+        for item in &module.body {
+            let ModuleItem::ModuleDecl(ModuleDecl::Import(import)) = item else {
+                continue;
+            };
+
+            let source = import.src.value.as_str().unwrap_or("");
+            if import.type_only || !self.config.import_sources.iter().any(|s| s == source) {
+                continue;
+            }
+
+            for specifier in &import.specifiers {
+                match specifier {
+                    ImportSpecifier::Named(named) if !named.is_type_only => {
+                        let imported = match &named.imported {
+                            Some(ModuleExportName::Ident(ident)) => ident.sym.as_str(),
+                            Some(ModuleExportName::Str(s)) => s.value.as_str().unwrap_or(""),
+                            None => named.local.sym.as_str(),
+                        };
+
+                        if self.is_silk_function(imported) {
+                            self.function_bindings.insert(named.local.to_id());
+                        }
+                    }
+                    ImportSpecifier::Namespace(ns) => {
+                        self.namespace_bindings.insert(ns.local.to_id());
+                    }
+                    _ => {}
+                }
             }
         }
+    }
 
-        // Apply custom prefix if provided (for branding)
-        if !config.class_prefix.is_empty() && config.class_prefix != "s" {
-            return format!("{}{}", config.class_prefix, short_hash);
-        }
+    fn is_silk_function(&self, name: &str) -> bool {
+        self.config.functions.iter().any(|f| f == name)
+    }
 
-        short_hash
-    } else {
-        // Development mode: descriptive class names
-        let prefix = if config.class_prefix.is_empty() {
-            "silk"
-        } else {
-            &config.class_prefix
+    /// Check if a CallExpr calls a Silk function imported into this module
+    fn is_css_call(&self, call: &CallExpr) -> bool {
+        let Callee::Expr(callee) = &call.callee else {
+            return false;
         };
 
-        // Create safe value for class name
-        let safe_value = value
-            .replace(' ', "_")
-            .replace('(', "")
-            .replace(')', "")
-            .replace('#', "")
-            .replace('.', "_")
-            .chars()
-            .take(10)
-            .collect::<String>();
+        match &**callee {
+            // Direct call: css() or an alias
+            Expr::Ident(ident) => self.function_bindings.contains(&ident.to_id()),
+            // Namespace call: silk.css()
+            Expr::Member(member) => match (&*member.obj, &member.prop) {
+                (Expr::Ident(obj), MemberProp::Ident(prop)) => {
+                    self.namespace_bindings.contains(&obj.to_id())
+                        && self.is_silk_function(prop.sym.as_str())
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    }
 
-        let short_hash = hash.chars().take(4).collect::<String>();
+    /// Collected [className, cssRule] pairs, in source order and deduplicated
+    pub fn css_rules(&self) -> &[(String, String)] {
+        &self.css_rules
+    }
 
-        format!("{}_{}_{}_{}", prefix, property, safe_value, short_hash)
+    /// Diagnostics for css() calls that could not be fully compiled
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
     }
-}
 
-/// Generate CSS rule for property-value pair
-pub fn generate_css_rule(class_name: &str, property: &str, value: &str) -> String {
-    let css_property = resolve_css_property(property);
-    let css_value = normalize_css_value(property, value);
+    /// Build the metadata emitted for this module.
+    /// Base rules come first, then media rules by min-width, so wider breakpoints
+    /// override narrower ones regardless of key order in the source.
+    pub fn metadata(&self) -> SilkMetadata {
+        let mut order: Vec<usize> = (0..self.css_rules.len()).collect();
+        order.sort_by(|&a, &b| self.rule_order[a].total_cmp(&self.rule_order[b]));
+
+        let css_rules: Vec<(String, String)> = order.iter().map(|&i| self.css_rules[i].clone()).collect();
+        SilkMetadata {
+            class_names: css_rules.iter().map(|(cls, _)| cls.clone()).collect(),
+            css_rules,
+            version: PLUGIN_VERSION.to_string(),
+            rule_sources: self.rule_sources.clone(),
+            ..Default::default()
+        }
+    }
 
-    format!(".{} {{ {}: {}; }}", class_name, css_property, css_value)
-}
+    /// Generate class names and collect CSS rules for static styles of the call at `call_span`
+    fn compile_styles(&mut self, styles: &[Style], call_span: Span) -> Vec<String> {
+        let mut class_names = Vec::new();
 
-/// Extract style properties from ObjectExpression
-pub fn extract_styles(obj: &ObjectLit) -> Vec<(String, String)> {
-    let mut styles = Vec::new();
+        for style in styles {
+            if let Some(breakpoint) = &style.breakpoint {
+                self.check_breakpoint(breakpoint, style.span);
+            }
+            // Classes are named after the value as written (`$primary`) like in Babel;
+            // `dedupeByDeclaration` names them after the emitted declaration instead
+            let resolved = self.resolve_tokens(style);
+            let named = if self.config.dedupe_by_declaration { &resolved } else { style };
+
+            let next = self.sequence.len();
+            let (property, value) = naming_declaration(&named.property, &named.value, &self.config);
+            let declaration = format!("{}:{}:{}", property, value, named.variant());
+            let index = *self.sequence.entry(declaration.clone()).or_insert(next);
+            let sequence = self.module_id.as_deref().map(|module| Sequence { module, index });
+            let mut class_name = named.sequenced_class_name(sequence, &self.config);
+
+            // Generate and collect CSS rule
+            let mut css_rule = resolved.css_rule(&class_name, &self.config);
+
+            // Another declaration already has this name: this one gets a longer name hashed
+            // from the declaration alone, so it is the same wherever the clash happens
+            if self.rule(&class_name).is_some_and(|existing| existing != css_rule) {
+                let fallback = fallback_class_name(&class_name, &declaration);
+                if self.collisions.insert(fallback.clone()) {
+                    self.diagnostics.push(Diagnostic::warning(
+                        DiagnosticCode::ClassCollision,
+                        style.span,
+                        format!(
+                            "class `{}` is already used for a different declaration, so this one is named `{}`; \
+                             set `hashSalt` or `classNameTemplate` to avoid the clash",
+                            class_name, fallback
+                        ),
+                    ));
+                }
+                css_rule = resolved.css_rule(&fallback, &self.config);
+                class_name = fallback;
+            }
 
-    for prop in &obj.props {
-        if let PropOrSpread::Prop(prop) = prop {
-            if let Prop::KeyValue(kv) = &**prop {
-                // Get property name
-                let prop_name = match &kv.key {
-                    PropName::Ident(ident) => ident.sym.as_str().to_string(),
-                    PropName::Str(s) => s.value.as_str().unwrap_or("").to_string(),
-                    _ => continue,
-                };
+            let order = breakpoint_order(style.breakpoint.as_deref(), &self.config);
+            self.add_rule(&class_name, css_rule, order);
+            self.rule_sources.push(RuleSource {
+                class_name: class_name.clone(),
+                span: style.span,
+                call_span,
+            });
+
+            // Aliases of one declaration share a class with `dedupeByDeclaration`
+            if !class_names.contains(&class_name) {
+                class_names.push(class_name);
+            }
+        }
 
-                // Get property value (only handle string and number literals for now)
-                let prop_value = match &*kv.value {
-                    Expr::Lit(Lit::Str(s)) => s.value.as_str().unwrap_or("").to_string(),
-                    Expr::Lit(Lit::Num(n)) => n.value.to_string(),
-                    _ => continue,
-                };
+        class_names
+    }
 
-                styles.push((prop_name, prop_value));
+    /// Record why a css() call could not be fully compiled.
+    /// In strict mode these are errors that fail the build.
+    fn report(&mut self, diagnostics: impl IntoIterator<Item = Diagnostic>) {
+        let strict = self.config.strict;
+        self.diagnostics.extend(diagnostics.into_iter().map(|mut diagnostic| {
+            if strict {
+                diagnostic.severity = Severity::Error;
             }
+            diagnostic
+        }));
+    }
+
+    /// Replace a design token reference with what `tokenOutput` asks for.
+    /// Unknown tokens never get here: extraction leaves them to the runtime.
+    fn resolve_tokens(&self, style: &Style) -> Style {
+        let mut style = style.clone();
+        let resolved = tokens::token_reference(&style.value, &self.config.tokens)
+            .and_then(|path| tokens::resolve_token(path, &self.config.tokens, self.config.token_output).ok());
+        if let Some(value) = resolved {
+            style.value = value;
         }
+        style
     }
 
-    styles
-}
+    /// Warn about breakpoint names missing from the config.
+    /// Keys starting with a digit (`'900px'`) are intentionally used as the min-width.
+    fn check_breakpoint(&mut self, breakpoint: &str, span: Span) {
+        let is_width = breakpoint.starts_with(|c: char| c.is_ascii_digit());
+        if breakpoint == "base" || is_width || self.config.breakpoints.contains_key(breakpoint) {
+            return;
+        }
 
-/// Main transform visitor
-pub struct SilkTransformVisitor {
-    config: Config,
-    css_rules: Vec<String>,
-}
+        self.diagnostics.push(Diagnostic::warning(
+            DiagnosticCode::UnknownBreakpoint,
+            span,
+            format!("unknown breakpoint `{}` is used as a min-width; add it to `breakpoints`", breakpoint),
+        ));
+    }
 
-impl SilkTransformVisitor {
-    pub fn new(config: Config) -> Self {
-        Self {
-            config,
-            css_rules: Vec::new(),
+    /// The rule already collected for a class name
+    fn rule(&self, class_name: &str) -> Option<&str> {
+        self.rule_index.get(class_name).map(|&i| self.css_rules[i].1.as_str())
+    }
+
+    /// Record a CSS rule once per class name
+    fn add_rule(&mut self, class_name: &str, rule: String, order: f64) {
+        if !self.rule_index.contains_key(class_name) {
+            self.rule_index.insert(class_name.to_string(), self.css_rules.len());
+            self.css_rules.push((class_name.to_string(), rule));
+            self.rule_order.push(order);
         }
     }
 }
 
 impl VisitMut for SilkTransformVisitor {
+    /// Collect Silk imports and module constants before visiting any call sites
+    fn visit_mut_module(&mut self, module: &mut Module) {
+        self.collect_imports(module);
+        self.constants = ModuleConstants::collect(module);
+        module.visit_mut_children_with(self);
+    }
+
     /// Visit expressions to transform css() calls
     fn visit_mut_expr(&mut self, expr: &mut Expr) {
         // First visit children (wrapped in safe recursion)
         expr.visit_mut_children_with(self);
 
         // Check if this is a css() call expression
-        if let Expr::Call(call) = expr {
-            if is_css_call(call) {
-                // Extract first argument (should be ObjectExpression)
-                if let Some(ExprOrSpread { spread: None, expr: arg }) = call.args.first() {
-                    if let Expr::Object(obj) = &**arg {
-                        // Extract styles from object (safe operation)
-                        let styles = extract_styles(obj);
-
-                        // If no styles extracted, bail out early
-                        if styles.is_empty() {
-                            return;
-                        }
+        let Expr::Call(call) = expr else {
+            return;
+        };
+        if !self.is_css_call(call) {
+            return;
+        }
 
-                        // Generate class names
-                        let mut class_names = Vec::new();
+        // Must have exactly one ObjectExpression argument
+        let call_span = call.span;
+        let [ExprOrSpread { spread: None, expr: arg }] = &mut call.args[..] else {
+            self.report([unsupported_arguments(call_span)]);
+            return;
+        };
+        let Expr::Object(obj) = &**arg else {
+            self.report([unsupported_arguments(call_span)]);
+            return;
+        };
 
-                        for (property, value) in &styles {
-                            let class_name = generate_class_name(
-                                property,
-                                value,
-                                &self.config,
-                            );
+        // Cannot extract - leave as runtime call
+        let extracted = match extract_static_styles(obj, &self.constants, &self.config.tokens) {
+            Ok(extracted) => extracted,
+            Err(diagnostic) => {
+                self.report([diagnostic]);
+                return;
+            }
+        };
 
-                            // Generate and collect CSS rule
-                            let css_rule = generate_css_rule(&class_name, property, value);
-                            self.css_rules.push(css_rule);
+        // Strict mode never falls back to runtime for part of a call
+        let partial = !extracted.diagnostics.is_empty();
+        self.report(extracted.diagnostics);
+        if partial && self.config.strict {
+            return;
+        }
 
-                            class_names.push(class_name);
-                        }
+        // Only dynamic props - leave as runtime call
+        if extracted.styles.is_empty() && !extracted.dynamic_props.is_empty() {
+            return;
+        }
 
-                        // Replace CallExpression with StringLiteral
-                        let class_string = class_names.join(" ");
-                        *expr = Expr::Lit(Lit::Str(Str {
-                            span: DUMMY_SP,
-                            value: class_string.into(),
-                            raw: None,
-                        }));
-                    }
-                }
-            }
+        let class_string = self.compile_styles(&extracted.styles, call_span).join(" ");
+
+        // Emitted literals keep the call's span so source maps still point at the css() call
+        if extracted.dynamic_props.is_empty() {
+            // Fully static - replace CallExpression with StringLiteral
+            *expr = Expr::Lit(Lit::Str(Str {
+                span: call_span,
+                value: class_string.into(),
+                raw: None,
+            }));
+        } else {
+            // Partial compilation - css({ dynamicProps }, "static classes")
+            let span = obj.span;
+            **arg = Expr::Object(ObjectLit {
+                span,
+                props: extracted.dynamic_props,
+            });
+            call.args.push(ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Lit(Lit::Str(Str {
+                    span: call_span,
+                    value: class_string.into(),
+                    raw: None,
+                }))),
+            });
         }
     }
 }
 
-/// Check if a CallExpr is a css() function call
-fn is_css_call(call: &CallExpr) -> bool {
-    match &call.callee {
-        Callee::Expr(expr) => match &**expr {
-            Expr::Ident(ident) => {
-                // Direct call: css()
-                ident.sym.as_ref() == "css"
-            }
-            Expr::Member(member) => {
-                // Member call: styled.css() or system.css()
-                match &member.prop {
-                    MemberProp::Ident(ident) => ident.sym.as_ref() == "css",
-                    _ => false,
-                }
+/// Error for a Silk call that does not take a single object literal
+fn unsupported_arguments(span: Span) -> Diagnostic {
+    Diagnostic::warning(
+        DiagnosticCode::UnsupportedArguments,
+        span,
+        "css() expects a single object literal; this call is left to the runtime",
+    )
+}
+
+/// Build the side-effect import specifier for a module's CSS from `pattern`
+pub fn css_import_specifier(pattern: &str, css: &str) -> String {
+    let mut specifier = pattern.replace("[hash]", &murmur_hash2(css));
+    if specifier.contains("[css]") {
+        specifier = specifier.replace("[css]", &encode_uri_component(css));
+    }
+    specifier
+}
+
+/// Percent-encode a string the way JavaScript's `encodeURIComponent` does
+fn encode_uri_component(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-' | b'_' | b'.' | b'!' | b'~' | b'*' | b'\'' | b'(' | b')' => {
+                encoded.push(byte as char)
             }
-            _ => false,
-        },
-        _ => false,
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
     }
+    encoded
+}
+
+/// Prepend `import "<specifier>"` to a module that produced CSS.
+/// The import goes after any directive prologue (`"use client"`) so directives keep working.
+pub fn inject_css_import(program: &mut Program, metadata: &SilkMetadata, pattern: &str) {
+    if metadata.css_rules.is_empty() {
+        return;
+    }
+
+    // Scripts cannot contain import declarations
+    let Program::Module(module) = program else {
+        return;
+    };
+
+    let specifier = css_import_specifier(pattern, &metadata.css());
+    let import = ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+        span: DUMMY_SP,
+        specifiers: vec![],
+        src: Box::new(Str {
+            span: DUMMY_SP,
+            value: specifier.into(),
+            raw: None,
+        }),
+        type_only: false,
+        with: None,
+        phase: Default::default(),
+    }));
+
+    let directives = module
+        .body
+        .iter()
+        .take_while(|item| {
+            matches!(
+                item,
+                ModuleItem::Stmt(Stmt::Expr(ExprStmt { expr, .. })) if matches!(&**expr, Expr::Lit(Lit::Str(_)))
+            )
+        })
+        .count();
+    module.body.insert(directives, import);
 }
 
 /// SWC plugin entry point
@@ -347,34 +2079,82 @@ fn is_css_call(call: &CallExpr) -> bool {
 pub fn process_transform(mut program: Program, metadata: TransformPluginProgramMetadata) -> Program {
     use swc_core::plugin::metadata::TransformPluginMetadataContextKind;
 
-    // Get filename (note: Turbopack only provides basename, not full path)
+    // A misconfigured build must fail rather than silently fall back to defaults
+    let config = match metadata.get_transform_plugin_config() {
+        Some(json) => match parse_config(&json) {
+            Ok(config) => config,
+            Err(errors) => {
+                for error in &errors {
+                    error.emit();
+                }
+                return program;
+            }
+        },
+        None => Config::default(),
+    };
+
+    // Turbopack only provides the basename, other hosts the full path
     let filename = metadata
         .get_context(&TransformPluginMetadataContextKind::Filename)
         .unwrap_or_default();
-
-    // Skip known Next.js internal files (by basename)
-    // Since Turbopack only gives basename, we can't check for node_modules path
-    if filename.starts_with("_app")
-        || filename.starts_with("_document")
-        || filename.contains("app-router")
-        || filename.contains("forbidden")
-        || filename.contains("not-found")
-        || filename.contains("unauthorized")
-        || filename.contains("global-error") {
+    let cwd = metadata.get_context(&TransformPluginMetadataContextKind::Cwd);
+    if !imports_silk(&program, &config) {
+        return program;
+    }
+    if !config.matches_file(&filename, cwd.as_deref()) {
+        if let Some(warning) = config.include_warning(&filename, cwd.as_deref()) {
+            warning.emit();
+        }
         return program;
     }
 
-    let config = metadata
-        .get_transform_plugin_config()
-        .and_then(|json_str| serde_json::from_str::<Config>(&json_str).ok())
-        .unwrap_or_default();
-
-    let mut visitor = SilkTransformVisitor::new(config);
+    let mut visitor = SilkTransformVisitor::new(config).with_filename(&filename, cwd.as_deref());
     program.visit_mut_with(&mut visitor);
+
+    for diagnostic in visitor.diagnostics() {
+        diagnostic.emit();
+    }
+
+    let mut silk_metadata = visitor.metadata();
+
+    // Dev builds trace every use of a class back to its property and point each rule
+    // at its css() call; production output never carries build paths
+    if !visitor.config.production {
+        let location = relative_path(&filename, cwd.as_deref()).unwrap_or(filename);
+        silk_metadata.locate_sources(&location, &metadata.source_map);
+        silk_metadata.annotate_sources(&location, &metadata.source_map);
+    }
+    if let Some(comments) = &metadata.comments {
+        emit_css_comment(&program, &silk_metadata, comments);
+    }
+
+    if visitor.config.css_output == CssOutput::Import {
+        inject_css_import(&mut program, &silk_metadata, &visitor.config.import_pattern);
+    }
+
     program
 }
 
+/// Attach the module's CSS as a leading `/* @silk-css {...} */` comment
+/// so bundler loaders can pick it up without re-running the transform
+pub fn emit_css_comment<C: Comments>(program: &Program, metadata: &SilkMetadata, comments: &C) {
+    if metadata.css_rules.is_empty() {
+        return;
+    }
+
+    let pos = program.span().lo;
+    comments.add_leading(
+        pos,
+        Comment {
+            kind: CommentKind::Block,
+            span: DUMMY_SP,
+            text: metadata.to_comment_text().into(),
+        },
+    );
+}
+
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison, clippy::format_in_format_args)]
 mod tests {
     use super::*;
 
@@ -382,7 +2162,7 @@ mod tests {
     fn test_config_default() {
         let config = Config::default();
         assert_eq!(config.production, false);
-        assert_eq!(config.class_prefix, "silk");
+        assert_eq!(config.class_prefix(), "silk");
     }
 
     #[test]
@@ -390,7 +2170,125 @@ mod tests {
         let json = r#"{"production": true, "classPrefix": "custom"}"#;
         let config: Config = serde_json::from_str(json).unwrap();
         assert_eq!(config.production, true);
-        assert_eq!(config.class_prefix, "custom");
+        assert_eq!(config.class_prefix(), "custom");
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config = parse_config(r#"{"production": true, "classPrefix": "app"}"#).unwrap();
+        assert!(config.production);
+        assert_eq!(config.class_prefix(), "app");
+
+        let errors = parse_config(r#"{"production": "yes", "classprefix": "app", "bogus": 1}"#).unwrap_err();
+        let messages: Vec<&str> = errors.iter().map(|error| error.message.as_str()).collect();
+        assert_eq!(messages.len(), 3, "{:#?}", messages);
+        assert!(errors.iter().all(|error| error.code == DiagnosticCode::InvalidConfig));
+        assert!(messages.contains(&"unknown config field `classprefix`, did you mean `classPrefix`?"));
+        assert!(messages.iter().any(|m| m.starts_with("unknown config field `bogus`, expected one of `production`")));
+        assert!(messages.iter().any(|m| m.starts_with("invalid value for config field `production`: invalid type")));
+
+        assert!(parse_config("[]").is_err());
+        assert!(parse_config("{").is_err());
+    }
+
+    #[test]
+    fn test_config_fields_match_config() {
+        // serde lists the expected fields when it rejects an unknown one
+        let err = serde_json::from_str::<Config>(r#"{"unknown": 1}"#).unwrap_err().to_string();
+        for field in CONFIG_FIELDS {
+            assert!(err.contains(&format!("`{}`", field)), "{} missing from {}", field, err);
+        }
+        assert_eq!(err.matches('`').count(), 2 * (CONFIG_FIELDS.len() + 1), "{}", err);
+
+        assert_eq!(closest_config_field("class_prefix"), Some("classPrefix"));
+        assert_eq!(closest_config_field("breakpoint"), Some("breakpoints"));
+        assert_eq!(closest_config_field("minify"), None);
+    }
+
+    #[test]
+    fn test_matches_file() {
+        let config = Config::default();
+        assert!(config.matches_file("not-found-banner.tsx", None));
+        assert!(config.matches_file("", None));
+        assert!(!config.matches_file("/app/node_modules/next/dist/client/components/not-found.js", None));
+
+        let config = Config {
+            include: vec!["src/**/*.tsx".to_string()],
+            exclude: vec!["**/*.test.tsx".to_string()],
+            ..Default::default()
+        };
+        assert!(config.matches_file("/repo/src/components/Button.tsx", Some("/repo")));
+        assert!(config.matches_file("src/Button.tsx", None));
+        assert!(!config.matches_file("/repo/src/Button.test.tsx", Some("/repo")));
+        assert!(!config.matches_file("/repo/lib/Button.tsx", Some("/repo")));
+        assert!(!config.matches_file("/repo/src/Button.ts", Some("/repo/")));
+        assert!(!config.matches_file("/repository/src/Button.tsx", Some("/repo")));
+        assert!(config.matches_file("C:\\repo\\src\\Button.tsx", Some("C:\\repo")));
+
+        // Turbopack passes basenames, which directory patterns never match
+        assert!(!config.matches_file("Button.tsx", Some("/repo")));
+        let warning = config.include_warning("Button.tsx", Some("/repo")).unwrap();
+        assert_eq!(warning.code, DiagnosticCode::InvalidConfig);
+        assert!(warning.message.contains("only passed the file's basename"));
+        assert!(config.include_warning("/repo/lib/Button.tsx", Some("/repo")).is_none());
+        assert!(config.include_warning("", None).is_none());
+        let config = Config { include: vec!["*.tsx".to_string()], ..Default::default() };
+        assert!(config.matches_file("Button.tsx", None));
+        assert!(config.include_warning("Button.ts", None).is_none());
+        assert!(Config::default().include_warning("Button.tsx", None).is_none());
+
+        let errors = parse_config(r#"{"breakpoints": {"md": "768px) { body"}}"#).unwrap_err();
+        assert_eq!(errors[0].message, "invalid breakpoint `md`: unbalanced `)`");
+
+        let errors = parse_config(r#"{"include": ["src/[.tsx"]}"#).unwrap_err();
+        assert!(errors[0].message.starts_with("invalid glob pattern `src/[.tsx`"));
+    }
+
+    #[test]
+    fn test_annotate_css_rule() {
+        assert_eq!(
+            annotate_css_rule(".a { color: red; }", "src/Button.tsx:42:10"),
+            "/* src/Button.tsx:42:10 */ .a { color: red; }"
+        );
+        assert_eq!(annotate_css_rule(".a {}", "weird*/name.tsx:1:1"), "/* weird* /name.tsx:1:1 */ .a {}");
+
+        assert_eq!(relative_path("/repo/src/a.tsx", Some("/repo")).as_deref(), Some("src/a.tsx"));
+        assert_eq!(relative_path("C:\\repo\\src\\a.tsx", Some("C:\\repo\\")).as_deref(), Some("src/a.tsx"));
+        assert_eq!(relative_path("/other/a.tsx", Some("/repo")), None);
+        assert_eq!(relative_path("a.tsx", None), None);
+    }
+
+    #[test]
+    fn test_config_import_sources() {
+        let config = Config::default();
+        assert_eq!(config.import_sources, vec!["@sylphx/silk"]);
+        assert_eq!(config.functions, vec!["css"]);
+
+        let json = r#"{"importSources": ["@acme/styles"], "functions": ["css", "sx"]}"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(config.import_sources, vec!["@acme/styles"]);
+        assert_eq!(config.functions, vec!["css", "sx"]);
+    }
+
+    #[test]
+    fn test_config_css_output() {
+        let config: Config = serde_json::from_str(r#"{"cssOutput": "import"}"#).unwrap();
+        assert_eq!(config.css_output, CssOutput::Import);
+        assert_eq!(config.import_pattern, "virtual:silk/[hash].css");
+        assert_eq!(Config::default().css_output, CssOutput::Comment);
+    }
+
+    #[test]
+    fn test_css_import_specifier() {
+        let css = ".a { color: red; }";
+        let specifier = css_import_specifier("virtual:silk/[hash].css", css);
+        assert_eq!(specifier, format!("virtual:silk/{}.css", murmur_hash2(css)));
+
+        // Different CSS produces a different module
+        assert_ne!(specifier, css_import_specifier("virtual:silk/[hash].css", ".b { color: blue; }"));
+
+        assert_eq!(encode_uri_component("a b/#%"), "a%20b%2F%23%25");
+        assert_eq!(encode_uri_component("(a)*!~'"), "(a)*!~'");
     }
 
     #[test]
@@ -425,6 +2323,281 @@ mod tests {
         assert_eq!(normalize_css_value("color", "red"), "red");
     }
 
+    #[test]
+    fn test_spacing_scale() {
+        for property in ["p", "mx", "marginTop", "gap", "rowGap", "inset", "insetInline", "top", "right", "bottom", "left"] {
+            assert!(is_spacing_property(property), "{}", property);
+        }
+        for property in ["maxWidth", "minHeight", "position", "perspective", "w", "translate"] {
+            assert!(!is_spacing_property(property), "{}", property);
+        }
+
+        let spacing: Spacing = serde_json::from_str(r#"{"multiplier": 8, "unit": "px", "scale": {"xs": "4px", "sm": "8px", "1": "2px"}}"#).unwrap();
+        assert_eq!(normalize_spaced_css_value("p", "2", Some(&spacing)), "16px");
+        assert_eq!(normalize_spaced_css_value("top", "-1", Some(&spacing)), "-8px");
+        assert_eq!(normalize_spaced_css_value("gap", "xs", Some(&spacing)), "4px");
+        assert_eq!(normalize_spaced_css_value("mx", "sm", Some(&spacing)), "8px");
+        // Scale steps win over the multiplier
+        assert_eq!(normalize_spaced_css_value("m", "1", Some(&spacing)), "2px");
+        // Other properties and unknown names are left alone
+        assert_eq!(normalize_spaced_css_value("w", "sm", Some(&spacing)), "sm");
+        assert_eq!(normalize_spaced_css_value("w", "2", Some(&spacing)), "2px");
+        assert_eq!(normalize_spaced_css_value("m", "auto", Some(&spacing)), "auto");
+
+        // Without a config, offsets keep `px` and named steps do not exist
+        assert_eq!(normalize_spaced_css_value("top", "4", None), "4px");
+        assert_eq!(normalize_spaced_css_value("insetInline", "4", None), "4px");
+        assert_eq!(normalize_spaced_css_value("p", "4", None), "1rem");
+        assert_eq!(normalize_spaced_css_value("p", "xs", None), "xs");
+
+        let errors = parse_config(r#"{"spacing": {"unit": "p x", "scale": {"xs": "1px; }"}}}"#).unwrap_err();
+        let messages: Vec<&str> = errors.iter().map(|error| error.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "invalid spacing unit `p x`: only letters and `%` are allowed",
+                "invalid spacing step `xs`: `;` outside a string",
+            ]
+        );
+        assert!(parse_config(r#"{"spacing": {"step": 4}}"#).is_err());
+    }
+
+    #[test]
+    fn test_resolve_pseudo_selector() {
+        assert_eq!(resolve_pseudo_selector("_hover"), ":hover");
+        assert_eq!(resolve_pseudo_selector("_focusVisible"), ":focus-visible");
+        assert_eq!(resolve_pseudo_selector("_placeholder"), "::placeholder");
+        assert_eq!(resolve_pseudo_selector("_before"), "::before");
+        assert_eq!(resolve_pseudo_selector("_odd"), ":nth-child(odd)");
+        assert_eq!(resolve_pseudo_selector("_even"), ":nth-child(even)");
+        assert_eq!(resolve_pseudo_selector("_firstOfType"), ":first-of-type");
+    }
+
+    #[test]
+    fn test_generate_pseudo_css_rule() {
+        let rule = generate_pseudo_css_rule("silk_hover_bg_red_a7f3", "_hover", "bg", "red", None);
+        assert_eq!(rule, ".silk_hover_bg_red_a7f3:hover { background-color: red; }");
+
+        let rule = generate_pseudo_css_rule("a", "_before", "content", "''", None);
+        assert_eq!(rule, ".a::before { content: ''; }");
+    }
+
+    #[test]
+    fn test_css_escape() {
+        assert_eq!(css_escape("silk_bg_red_a7f3"), "silk_bg_red_a7f3");
+        assert_eq!(css_escape("silk_width_100%_x1"), "silk_width_100\\%_x1");
+        assert_eq!(css_escape("a/b,c:d!e.f"), "a\\/b\\,c\\:d\\!e\\.f");
+        assert_eq!(css_escape("1a"), "\\31 a");
+        assert_eq!(css_escape("-1a"), "-\\31 a");
+        assert_eq!(css_escape("-"), "\\-");
+        assert_eq!(css_escape("a\u{0}b\u{1}"), "a\u{fffd}b\\1 ");
+        assert_eq!(css_escape("日本"), "日本");
+
+        assert_eq!(
+            generate_css_rule("silk_width_100%_x1", "width", "100%"),
+            ".silk_width_100\\%_x1 { width: 100%; }"
+        );
+    }
+
+    /// Consume a CSS ident-token from the start of `input` (CSS Syntax Level 3, §4.3.11),
+    /// returning the decoded identifier and the remaining input
+    fn consume_ident(input: &str) -> Option<(String, &str)> {
+        let chars: Vec<char> = input.chars().collect();
+        let is_ident_start = |c: char| c.is_ascii_alphabetic() || c == '_' || c >= '\u{80}';
+        let is_valid_escape = |i: usize| chars.get(i) == Some(&'\\') && chars.get(i + 1).is_some_and(|c| *c != '\n');
+
+        // Check the input would start an identifier
+        let starts_ident = match chars.first()? {
+            '-' => chars.get(1).is_some_and(|c| is_ident_start(*c) || *c == '-') || is_valid_escape(1),
+            '\\' => is_valid_escape(0),
+            c => is_ident_start(*c),
+        };
+        if !starts_ident {
+            return None;
+        }
+
+        let mut ident = String::new();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            if is_ident_start(c) || c.is_ascii_digit() || c == '-' {
+                ident.push(c);
+                i += 1;
+            } else if is_valid_escape(i) {
+                i += 1;
+                let hex: String = chars[i..].iter().take_while(|c| c.is_ascii_hexdigit()).take(6).collect();
+                if hex.is_empty() {
+                    ident.push(chars[i]);
+                    i += 1;
+                } else {
+                    i += hex.len();
+                    if chars.get(i).is_some_and(|c| c.is_whitespace()) {
+                        i += 1;
+                    }
+                    let code = u32::from_str_radix(&hex, 16).unwrap();
+                    ident.push(char::from_u32(code).filter(|c| *c != '\0').unwrap_or('\u{fffd}'));
+                }
+            } else {
+                break;
+            }
+        }
+
+        let rest: String = chars[i..].iter().collect();
+        Some((ident, &input[input.len() - rest.len()..]))
+    }
+
+    #[test]
+    fn test_fuzz_emitted_selectors_parse() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        const ALPHABET: &[char] = &['a', 'Z', '0', '9', '-', '_', '%', ',', '/', ':', '!', '.', '#', '(', ')', ' ', '\'', '"', '\\', '{', '}', ';', '@', '*', '+', '~', '>', '[', ']', '=', '$', '&', '|', '^', '\u{7f}', '\u{1}', 'é', '日', '👋'];
+
+        let mut rng = StdRng::seed_from_u64(0xc55);
+        let mut random = |max: usize| -> String {
+            let len = rng.gen_range(0..=max);
+            (0..len).map(|_| ALPHABET[rng.gen_range(0..ALPHABET.len())]).collect()
+        };
+
+        for i in 0..3000 {
+            let config = Config {
+                production: i % 4 == 3,
+                class_prefix: if i % 5 == 0 { Some(random(4)) } else { None },
+                class_naming: if i % 2 == 0 { ClassNaming::Compact } else { ClassNaming::Babel },
+                ..Default::default()
+            };
+            let (property, value, variant) = ("width", random(12), random(3));
+
+            let class_name = generate_variant_class_name(property, &value, &variant, &config);
+            if class_name.is_empty() {
+                continue;
+            }
+            let rule = generate_css_rule(&class_name, property, &value);
+
+            let selector = rule.strip_prefix('.').unwrap();
+            let (ident, rest) = consume_ident(selector)
+                .unwrap_or_else(|| panic!("invalid selector for {:?}: {}", class_name, rule));
+            assert_eq!(ident, class_name, "rule: {}", rule);
+            assert!(rest.starts_with(" { "), "selector does not end after the class: {}", rule);
+        }
+    }
+
+    #[test]
+    fn test_validate_css_value() {
+        for value in [
+            "red",
+            "rgb(255, 0, 0)",
+            "calc(100% - (2 * var(--gap)))",
+            "'a;b{c}'",
+            "\"it's\"",
+            "url(\"data:image/svg+xml;utf8,<svg></svg>\")",
+            "attr(data-x) /* ; } */",
+            "'\\''",
+            "\\;",
+            "[full-start] 1fr [full-end]",
+            "url(data:image/png;base64,iVBORw0KGgo=)",
+            "URL( a;b}c.png )",
+            "url(a\\)b.png), myurl(x)",
+            "url( 'a;b' )",
+            "",
+        ] {
+            assert_eq!(validate_css_value(value), Ok(()), "{:?}", value);
+        }
+
+        let rejected = [
+            ("red; } body { display:none", "`;` outside a string"),
+            ("red}", "`}` outside a string"),
+            ("'\\'' ; ", "`;` outside a string"),
+            ("calc(1px", "missing `)`"),
+            ("1px)", "unbalanced `)`"),
+            ("[a)", "unbalanced `)`"),
+            ("'red", "unterminated string starting with `'`"),
+            ("\"a\nb\"", "unterminated string starting with `\"`"),
+            ("red /* ", "unterminated comment"),
+            ("red\\", "trailing `\\` escapes the end of the declaration"),
+            ("url(a.png", "missing `)`"),
+            ("url(a b)", "whitespace inside an unquoted `url()`"),
+            ("url(a'b)", "`'` inside an unquoted `url()`"),
+            ("myurl(a;b)", "`;` outside a string"),
+            ("url('a';b)", "`;` outside a string"),
+        ];
+        for (value, reason) in rejected {
+            assert_eq!(validate_css_value(value), Err(reason.to_string()), "{:?}", value);
+        }
+
+        assert!(is_css_ident("--brand-color"));
+        assert!(is_css_ident("_focusVisible"));
+        assert!(!is_css_ident("color: red; } .x { color"));
+        assert!(!is_css_ident(""));
+    }
+
+    #[test]
+    fn test_variant_class_name() {
+        let config = Config::default();
+        let base = generate_class_name("bg", "red", &config);
+        let hover = generate_variant_class_name("bg", "red", "hover", &config);
+
+        assert!(hover.starts_with("silk_hover_bg_red_"));
+        assert_ne!(&base[base.len() - 4..], &hover[hover.len() - 4..]);
+        assert_ne!(
+            hash_property_value("bg", "red", "hover", ""),
+            hash_property_value("bg", "red", "focus", "")
+        );
+    }
+
+    #[test]
+    fn test_config_breakpoints() {
+        let config = Config::default();
+        assert_eq!(config.breakpoints.len(), 5);
+        assert_eq!(config.breakpoints["md"], "768px");
+        assert_eq!(config.breakpoints["2xl"], "1536px");
+
+        let json = r#"{"breakpoints": {"tablet": "600px"}}"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(config.breakpoints.len(), 1);
+        assert_eq!(config.breakpoints["tablet"], "600px");
+    }
+
+    #[test]
+    fn test_wrap_breakpoint() {
+        let config = Config::default();
+        let rule = ".a { width: 50%; }".to_string();
+
+        assert_eq!(wrap_breakpoint(rule.clone(), "base", &config), ".a { width: 50%; }");
+        assert_eq!(
+            wrap_breakpoint(rule.clone(), "md", &config),
+            "@media (min-width: 768px) { .a { width: 50%; } }"
+        );
+        assert_eq!(
+            wrap_breakpoint(rule, "900px", &config),
+            "@media (min-width: 900px) { .a { width: 50%; } }"
+        );
+    }
+
+    #[test]
+    fn test_breakpoint_order() {
+        let config = Config::default();
+        assert_eq!(breakpoint_order(None, &config), 0.0);
+        assert_eq!(breakpoint_order(Some("base"), &config), 0.0);
+        assert_eq!(breakpoint_order(Some("sm"), &config), 640.0);
+        assert_eq!(breakpoint_order(Some("2xl"), &config), 1536.0);
+        assert_eq!(breakpoint_order(Some("900px"), &config), 900.0);
+        assert_eq!(breakpoint_order(Some("40em"), &config), 640.0);
+        assert_eq!(breakpoint_order(Some("calc(1px)"), &config), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_negative_values() {
+        assert_eq!(normalize_css_value("mt", "-4"), "-1rem");
+        assert_eq!(normalize_css_value("zIndex", "-1"), "-1");
+        assert_eq!(normalize_css_value("top", "-10"), "-10px");
+
+        let config = Config::default();
+        let negative = generate_class_name("mt", "-4", &config);
+        let positive = generate_class_name("mt", "4", &config);
+        assert!(negative.starts_with("silk_mt_4_"));
+        assert_ne!(negative, positive);
+    }
+
     #[test]
     fn test_base36_encode() {
         assert_eq!(base36_encode(0), "0");
@@ -448,11 +2621,63 @@ mod tests {
         assert!(hash1.chars().all(|c| c.is_ascii_alphanumeric() && !c.is_uppercase()));
     }
 
+    /// MurmurHash2 over arbitrary 32-bit units, for checking `murmur_hash2` against
+    fn murmur_units(units: impl IntoIterator<Item = u32>) -> String {
+        let mut h: u32 = 0;
+        for c in units {
+            h = (h ^ c).wrapping_mul(0x5bd1e995);
+            h ^= h >> 13;
+        }
+        base36_encode(h)
+    }
+
+    #[test]
+    fn test_murmur_hash2_js_corpus() {
+        // Recorded from `murmurHash2` in @sylphx/silk (packages/core/src/production.ts)
+        let corpus: Vec<serde_json::Value> =
+            serde_json::from_str(include_str!("../tests/fixtures/murmur-hash.json")).unwrap();
+
+        for case in &corpus {
+            let input = case["input"].as_str().unwrap();
+            assert_eq!(murmur_hash2(input), case["hash"].as_str().unwrap(), "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_murmur_hash2_properties() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(0x5111c);
+        for _ in 0..2000 {
+            let len = rng.gen_range(0..16);
+            let s: String = (0..len)
+                .map(|_| match rng.gen_range(0..3) {
+                    0 => rng.gen_range(' '..='~'),
+                    1 => rng.gen_range('\u{80}'..='\u{ffff}'),
+                    _ => rng.gen::<char>(),
+                })
+                .collect();
+
+            // Hashing follows JavaScript's UTF-16 view of the string
+            assert_eq!(murmur_hash2(&s), murmur_units(s.encode_utf16().map(u32::from)), "{:?}", s);
+
+            // BMP-only strings hash exactly as they did per code point
+            if s.chars().all(|c| c.len_utf16() == 1) {
+                assert_eq!(murmur_hash2(&s), murmur_units(s.chars().map(u32::from)), "{:?}", s);
+            }
+        }
+
+        // An astral character is hashed as its surrogate pair
+        assert_eq!(murmur_hash2("👋"), murmur_units([0xd83d, 0xdc4b]));
+        assert_ne!(murmur_hash2("👋"), murmur_units([0x1f44b]));
+    }
+
     #[test]
     fn test_generate_class_name_dev_mode() {
         let config = Config {
             production: false,
-            class_prefix: "silk".to_string(),
+            class_prefix: Some("silk".to_string()),
+            ..Default::default()
         };
         let class_name = generate_class_name("bg", "red", &config);
         assert!(class_name.starts_with("silk_bg_red_"));
@@ -463,7 +2688,8 @@ mod tests {
     fn test_generate_class_name_production_mode() {
         let config = Config {
             production: true,
-            class_prefix: String::new(),
+            class_prefix: Some(String::new()),
+            ..Default::default()
         };
         let class_name = generate_class_name("bg", "red", &config);
 
@@ -481,7 +2707,8 @@ mod tests {
     fn test_digit_mapping() {
         let config = Config {
             production: true,
-            class_prefix: String::new(),
+            class_prefix: Some(String::new()),
+            ..Default::default()
         };
 
         // Test multiple properties to ensure we hit some that start with digits
@@ -506,7 +2733,8 @@ mod tests {
     fn test_production_with_custom_prefix() {
         let config = Config {
             production: true,
-            class_prefix: "app".to_string(),
+            class_prefix: Some("app".to_string()),
+            ..Default::default()
         };
         let class_name = generate_class_name("bg", "red", &config);
 
@@ -517,6 +2745,146 @@ mod tests {
         assert!(class_name.len() >= 9 && class_name.len() <= 11);
     }
 
+    #[test]
+    fn test_class_name_strategy() {
+        let config = Config::default();
+        assert_eq!(config.strategy(), ClassNameStrategy::Readable);
+
+        let config = Config { production: true, ..Default::default() };
+        assert_eq!(config.strategy(), ClassNameStrategy::Hash);
+
+        let config: Config = serde_json::from_str(r#"{"classNameStrategy": "sequential-minimal"}"#).unwrap();
+        assert_eq!(config.strategy(), ClassNameStrategy::SequentialMinimal);
+
+        // An explicit strategy wins over `production`
+        let config = Config {
+            class_name_strategy: Some(ClassNameStrategy::Readable),
+            production: true,
+            ..Default::default()
+        };
+        assert!(generate_class_name("bg", "red", &config).starts_with("silk_bg_red_"));
+        let config = Config { class_name_strategy: Some(ClassNameStrategy::Hash), ..Default::default() };
+        assert_eq!(generate_class_name("bg", "red", &config), "silkoqmaqr");
+    }
+
+    #[test]
+    fn test_sequential_minimal_class_names() {
+        assert_eq!(minimal_ident(0), "a");
+        assert_eq!(minimal_ident(25), "z");
+        assert_eq!(minimal_ident(26), "a1");
+        assert_eq!(minimal_ident(27), "b1");
+        assert_eq!(minimal_ident(26 * 36), "a10");
+
+        let config = Config {
+            class_name_strategy: Some(ClassNameStrategy::SequentialMinimal),
+            ..Default::default()
+        };
+        let module = module_id("src/Button.tsx", "");
+        assert_eq!(module.len(), 7);
+        assert!(module.chars().next().unwrap().is_ascii_alphabetic());
+        assert_ne!(module, module_id("src/Card.tsx", ""));
+
+        let sequence = Sequence { module: &module, index: 27 };
+        assert_eq!(
+            generate_sequenced_class_name("bg", "red", "", Some(sequence), &config),
+            format!("{}b1", module)
+        );
+        let prefixed = Config { class_prefix: Some("ui-".to_string()), ..config.clone() };
+        assert_eq!(
+            generate_sequenced_class_name("bg", "red", "", Some(sequence), &prefixed),
+            format!("ui-{}b1", module)
+        );
+
+        // Without a module, names cannot be sequential
+        let hash = Config { class_name_strategy: Some(ClassNameStrategy::Hash), ..Default::default() };
+        assert_eq!(generate_class_name("bg", "red", &config), generate_class_name("bg", "red", &hash));
+    }
+
+    #[test]
+    fn test_class_name_template() {
+        let config = Config {
+            class_name_template: Some("[prefix]-[prop]-[hash:6]".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(generate_class_name("bg", "red", &config), "silk-bg-oqmaqr");
+
+        // `[variant]` expands to nothing for base styles, taking its separator with it
+        let config = Config {
+            class_name_template: Some("[variant]-[prop]_[value]_[hash:4]".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(generate_class_name("bg", "red", &config), "bg_red_oqma");
+        assert!(generate_variant_class_name("bg", "red", "hover", &config).starts_with("hover-bg_red_"));
+
+        let config = Config {
+            class_name_template: Some("x[index]".to_string()),
+            ..Default::default()
+        };
+        let sequence = Sequence { module: "gabc123", index: 2 };
+        assert_eq!(generate_sequenced_class_name("bg", "red", "", Some(sequence), &config), "xgabc123c");
+        assert_eq!(generate_class_name("bg", "red", &config), "xoqmaqr");
+    }
+
+    #[test]
+    fn test_validate_class_name_template() {
+        assert!(validate_class_name_template("[prefix]-[prop]-[hash:6]").is_ok());
+        assert!(validate_class_name_template("[index]").is_ok());
+
+        assert!(validate_class_name_template("[prefix]-[prop]").unwrap_err().contains("needs `[hash]`"));
+        assert!(validate_class_name_template("[hash:0]").unwrap_err().contains("positive length"));
+        assert!(validate_class_name_template("[hash]-[name]").unwrap_err().contains("unknown placeholder `[name]`"));
+        assert!(validate_class_name_template("[hash").unwrap_err().contains("unclosed"));
+        assert!(validate_class_name_template("[hash].x").unwrap_err().contains("only letters"));
+
+        let errors = parse_config(r#"{"classNameTemplate": "[prop]"}"#).unwrap_err();
+        assert_eq!(errors[0].code, DiagnosticCode::InvalidConfig);
+        assert!(errors[0].message.contains("invalid classNameTemplate `[prop]`"));
+    }
+
+    #[test]
+    fn test_hash_salt() {
+        let salted = |salt: &str, production: bool| Config {
+            hash_salt: salt.to_string(),
+            production,
+            ..Default::default()
+        };
+
+        // No salt keeps the Babel-compatible hashes
+        assert_eq!(generate_class_name("bg", "red", &salted("", true)), "silkoqmaqr");
+        assert_ne!(generate_class_name("bg", "red", &salted("app", true)), "silkoqmaqr");
+        assert_ne!(
+            generate_class_name("bg", "red", &salted("app", true)),
+            generate_class_name("bg", "red", &salted("admin", true))
+        );
+
+        // Development names show the salt after the prefix
+        assert!(generate_class_name("bg", "red", &salted("app", false)).starts_with("silk_app_bg_red_"));
+        assert!(generate_variant_class_name("bg", "red", "hover", &salted("app", false)).starts_with("silk_app_hover_bg_red_"));
+
+        let errors = parse_config(r#"{"hashSalt": "my app"}"#).unwrap_err();
+        assert!(errors[0].message.contains("invalid hashSalt `my app`"));
+    }
+
+    #[test]
+    fn test_dedupe_by_declaration() {
+        let config = Config { production: true, ..Default::default() };
+        assert_ne!(generate_class_name("bg", "red", &config), generate_class_name("backgroundColor", "red", &config));
+
+        let config = Config { production: true, dedupe_by_declaration: true, ..Default::default() };
+        let name = generate_class_name("bg", "red", &config);
+        assert_eq!(generate_class_name("bgColor", "red", &config), name);
+        assert_eq!(generate_class_name("backgroundColor", "red", &config), name);
+        assert_eq!(generate_class_name("background-color", "red", &config), name);
+        assert_ne!(generate_variant_class_name("bg", "red", "hover", &config), name);
+
+        // Values are compared after normalization
+        assert_eq!(generate_class_name("p", "4", &config), generate_class_name("padding", "1rem", &config));
+        assert_ne!(generate_class_name("p", "4", &config), generate_class_name("padding", "4px", &config));
+
+        let config = Config { dedupe_by_declaration: true, ..Default::default() };
+        assert!(generate_class_name("bg", "red", &config).starts_with("silk_background-color_red_"));
+    }
+
     #[test]
     fn test_generate_css_rule() {
         let class_name = "silk_bg_red_a7f3";
@@ -527,8 +2895,8 @@ mod tests {
     #[test]
     fn test_hash_consistency() {
         // Test that hashing the same property-value produces the same result
-        let hash1 = hash_property_value("background-color", "red", "");
-        let hash2 = hash_property_value("background-color", "red", "");
+        let hash1 = hash_property_value("background-color", "red", "", "");
+        let hash2 = hash_property_value("background-color", "red", "", "");
         assert_eq!(hash1, hash2);
     }
 
@@ -538,7 +2906,8 @@ mod tests {
         // These expected values come from running test-hash-consistency.mjs
         let config = Config {
             production: true,
-            class_prefix: String::new(),
+            class_prefix: Some(String::new()),
+            ..Default::default()
         };
 
         let test_cases = vec![
@@ -585,7 +2954,8 @@ mod tests {
         // Verifies more comprehensive set of properties and values
         let config = Config {
             production: true,
-            class_prefix: String::new(),
+            class_prefix: Some(String::new()),
+            ..Default::default()
         };
 
         let test_cases = vec![
@@ -661,7 +3031,8 @@ mod tests {
         // Test that NO class names start with digits across many test cases
         let config = Config {
             production: true,
-            class_prefix: String::new(),
+            class_prefix: Some(String::new()),
+            ..Default::default()
         };
 
         let test_values = vec![
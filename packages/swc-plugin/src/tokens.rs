@@ -0,0 +1,172 @@
+//! Design token references in style values
+//!
+//! `tokens` in the plugin config is a tree of values (`{"colors": {"primary": "#3b82f6"}}`).
+//! A style value that is a whole reference, `$colors.primary` or `colors.primary`, is
+//! replaced by the token's value or by the `var(--colors-primary)` custom property.
+
+use crate::evaluate::format_number;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// A design token: a value, or a group of named tokens
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum Token {
+    Value(String),
+    Number(f64),
+    Group(HashMap<String, Token>),
+}
+
+/// How token references are emitted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenOutput {
+    /// The token's value (`#3b82f6`)
+    #[default]
+    Value,
+
+    /// A custom property named after the token's path (`var(--colors-primary)`),
+    /// for themes that define the properties themselves
+    Var,
+}
+
+/// Token path a style value refers to, if any.
+/// `$path` always is a reference; a bare dotted path only when it starts with a token
+/// group, so values like `1.5` are left alone.
+pub(crate) fn token_reference<'a>(value: &'a str, tokens: &HashMap<String, Token>) -> Option<&'a str> {
+    if let Some(path) = value.strip_prefix('$') {
+        return Some(path);
+    }
+
+    let (group, _) = value.split_once('.')?;
+    matches!(tokens.get(group), Some(Token::Group(_))).then_some(value)
+}
+
+/// Resolve a token path (`colors.primary`) to the CSS it stands for
+pub(crate) fn resolve_token(path: &str, tokens: &HashMap<String, Token>, output: TokenOutput) -> Result<String, String> {
+    let unknown = || format!("unknown token `{}`", path);
+
+    let mut token: Option<&Token> = None;
+    for segment in path.split('.') {
+        let group = match token {
+            None => tokens,
+            Some(Token::Group(group)) => group,
+            Some(_) => return Err(unknown()),
+        };
+        token = Some(group.get(segment).ok_or_else(unknown)?);
+    }
+
+    let value = match token {
+        Some(Token::Value(value)) => value.clone(),
+        Some(Token::Number(n)) => format_number(*n),
+        Some(Token::Group(_)) => return Err(format!("`{}` is a group of tokens, not a value", path)),
+        None => return Err(unknown()),
+    };
+
+    Ok(match output {
+        TokenOutput::Value => value,
+        TokenOutput::Var => format!("var(--{})", path.replace('.', "-")),
+    })
+}
+
+/// Why a style value cannot be compiled, if it refers to a token that does not resolve
+pub(crate) fn unknown_token(value: &str, tokens: &HashMap<String, Token>) -> Option<String> {
+    let path = token_reference(value, tokens)?;
+    resolve_token(path, tokens, TokenOutput::Value).err()
+}
+
+/// Check every token: names must work as custom property segments and values must
+/// stay inside their declaration. Returns one message per invalid token.
+pub(crate) fn validate_tokens(tokens: &HashMap<String, Token>) -> Vec<String> {
+    let mut errors = Vec::new();
+    validate_group(tokens, "", &mut errors);
+    errors
+}
+
+fn validate_group(group: &HashMap<String, Token>, parent: &str, errors: &mut Vec<String>) {
+    let mut names: Vec<&String> = group.keys().collect();
+    names.sort();
+
+    for name in names {
+        let path = if parent.is_empty() { name.clone() } else { format!("{}.{}", parent, name) };
+        if !crate::is_css_ident(name) {
+            errors.push(format!("invalid token name `{}`: only letters, digits, `-` and `_` are allowed", path));
+            continue;
+        }
+
+        match &group[name] {
+            Token::Group(nested) => validate_group(nested, &path, errors),
+            Token::Value(value) => {
+                if let Err(reason) = crate::validate_css_value(value) {
+                    errors.push(format!("invalid token `{}`: {}", path, reason));
+                }
+            }
+            Token::Number(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens() -> HashMap<String, Token> {
+        serde_json::from_str(
+            r##"{
+                "primary": "#000",
+                "colors": { "primary": "#3b82f6", "gray": { "100": "#f3f4f6" } },
+                "spacing": { "4": 16 }
+            }"##,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_token_reference() {
+        let tokens = tokens();
+        assert_eq!(token_reference("$colors.primary", &tokens), Some("colors.primary"));
+        assert_eq!(token_reference("colors.primary", &tokens), Some("colors.primary"));
+        assert_eq!(token_reference("$primary", &tokens), Some("primary"));
+        assert_eq!(token_reference("colors.typo", &tokens), Some("colors.typo"));
+
+        assert_eq!(token_reference("primary", &tokens), None);
+        assert_eq!(token_reference("1.5", &tokens), None);
+        assert_eq!(token_reference("url(a.png)", &tokens), None);
+    }
+
+    #[test]
+    fn test_resolve_token() {
+        let tokens = tokens();
+        let value = |path| resolve_token(path, &tokens, TokenOutput::Value);
+        assert_eq!(value("colors.primary").unwrap(), "#3b82f6");
+        assert_eq!(value("colors.gray.100").unwrap(), "#f3f4f6");
+        assert_eq!(value("primary").unwrap(), "#000");
+        assert_eq!(value("spacing.4").unwrap(), "16");
+
+        assert_eq!(value("colors.typo").unwrap_err(), "unknown token `colors.typo`");
+        assert_eq!(value("primary.dark").unwrap_err(), "unknown token `primary.dark`");
+        assert_eq!(value("").unwrap_err(), "unknown token ``");
+        assert!(value("colors").unwrap_err().contains("group of tokens"));
+
+        assert_eq!(
+            resolve_token("colors.gray.100", &tokens, TokenOutput::Var).unwrap(),
+            "var(--colors-gray-100)"
+        );
+    }
+
+    #[test]
+    fn test_validate_tokens() {
+        assert!(validate_tokens(&tokens()).is_empty());
+
+        let tokens: HashMap<String, Token> =
+            serde_json::from_str(r#"{ "colors": { "bad": "red; } body {", "a b": "red" }, "space": { "0.5": "2px" } }"#).unwrap();
+        assert_eq!(
+            validate_tokens(&tokens),
+            vec![
+                "invalid token name `colors.a b`: only letters, digits, `-` and `_` are allowed",
+                "invalid token `colors.bad`: `;` outside a string",
+                "invalid token name `space.0.5`: only letters, digits, `-` and `_` are allowed",
+            ]
+        );
+    }
+}
@@ -1,7 +1,82 @@
+use swc_core::common::comments::SingleThreadedComments;
+use swc_core::common::{Mark, SourceMapper, Spanned};
+use swc_core::ecma::ast::Program;
+use swc_core::ecma::parser::{Syntax, TsSyntax};
+use swc_core::ecma::transforms::base::resolver;
+use swc_core::ecma::transforms::testing::Tester;
+use swc_core::ecma::visit::VisitMutWith;
+use swc_plugin_silk::{
+    emit_css_comment, inject_css_import, ClassNameStrategy, Config, CssOutput, DiagnosticCode, Severity,
+    SilkMetadata, SilkTransformVisitor,
+};
+
+/// Run the visitor over `src` and return the printed output plus module metadata
+fn transform(src: &str, config: Config) -> (String, SilkMetadata) {
+    let (code, metadata, _) = transform_with_diagnostics(src, config);
+    (code, metadata)
+}
+
+/// Like [`transform`], also returning each diagnostic's code, severity and the source text it points at
+fn transform_with_diagnostics(
+    src: &str,
+    config: Config,
+) -> (String, SilkMetadata, Vec<(DiagnosticCode, Severity, String)>) {
+    transform_file(src, config, "/app/src/input.tsx")
+}
+
+/// Like [`transform_with_diagnostics`] for a module at `filename`, with `/app` as the host's cwd
+fn transform_file(
+    src: &str,
+    config: Config,
+    filename: &str,
+) -> (String, SilkMetadata, Vec<(DiagnosticCode, Severity, String)>) {
+    Tester::run(|tester| {
+        let syntax = Syntax::Typescript(TsSyntax {
+            tsx: true,
+            ..Default::default()
+        });
+        let mut program = Program::Module(tester.with_parser("input.tsx", syntax, src, |p| p.parse_module())?);
+
+        // SWC resolves bindings before running plugins
+        program.visit_mut_with(&mut resolver(Mark::new(), Mark::new(), false));
+
+        let mut visitor = SilkTransformVisitor::new(config.clone()).with_filename(filename, Some("/app"));
+        program.visit_mut_with(&mut visitor);
+
+        let metadata = visitor.metadata();
+        let comments = std::rc::Rc::new(SingleThreadedComments::default());
+        emit_css_comment(&program, &metadata, &*comments);
+        if config.css_output == CssOutput::Import {
+            inject_css_import(&mut program, &metadata, &config.import_pattern);
+        }
+
+        let diagnostics = visitor
+            .diagnostics()
+            .iter()
+            .map(|diagnostic| {
+                let snippet = tester.cm.span_to_snippet(diagnostic.span).unwrap_or_default();
+                (diagnostic.code, diagnostic.severity, snippet)
+            })
+            .collect();
+
+        Ok((tester.print(&program, &comments), metadata, diagnostics))
+    })
+}
+
 #[test]
 fn test_basic_transformation() {
-    // Test that css() calls are detected and transformed
-    // This is a placeholder - actual integration tests would parse fixtures
+    let (code, metadata) = transform(
+        "import { css } from '@sylphx/silk';\nconst button = css({ bg: 'red' });",
+        Config::default(),
+    );
+
+    let class_name = &metadata.class_names[0];
+    assert!(class_name.starts_with("silk_bg_red_"));
+    assert!(code.contains(&format!("const button = \"{}\";", class_name)));
+    assert_eq!(
+        metadata.css_rules,
+        vec![(class_name.clone(), format!(".{} {{ background-color: red; }}", class_name))]
+    );
 }
 
 #[test]
@@ -66,6 +141,10 @@ fn test_unit_handling() {
     assert_eq!(normalize_css_value("top", "50"), "50px");
     assert_eq!(normalize_css_value("left", "25"), "25px");
 
+    // Without `spacing`, any property written with a leading `p` or `m` uses rem steps like Babel
+    assert_eq!(normalize_css_value("maxWidth", "50"), "12.5rem");
+    assert_eq!(normalize_css_value("perspective", "4"), "1rem");
+
     // String values should be unchanged
     assert_eq!(normalize_css_value("color", "red"), "red");
     assert_eq!(normalize_css_value("bg", "#ff0000"), "#ff0000");
@@ -89,7 +168,8 @@ fn test_class_name_generation() {
     // Test with different prefix
     let custom_config = Config {
         production: false,
-        class_prefix: "custom".to_string(),
+        class_prefix: Some("custom".to_string()),
+        ..Default::default()
     };
     let class2 = generate_class_name("p", "4", &custom_config);
     assert!(class2.starts_with("custom_p_4_"));
@@ -143,8 +223,57 @@ fn test_empty_object() {
 
 #[test]
 fn test_multiple_properties() {
-    // Test that multiple properties in one css() call all get transformed
-    // This would require parsing actual code, so it's a placeholder
+    let (code, metadata) = transform(
+        "import { css } from '@sylphx/silk';\nconst a = css({ bg: 'red', p: 4 });\nconst b = css({ bg: 'red' });",
+        Config::default(),
+    );
+
+    // Shared declarations are only emitted once per module
+    assert_eq!(metadata.css_rules.len(), 2);
+    assert_eq!(metadata.class_names.len(), 2);
+    assert!(code.contains(&format!("const a = \"{} {}\";", metadata.class_names[0], metadata.class_names[1])));
+}
+
+#[test]
+fn test_css_metadata_comment() {
+    let (code, metadata) = transform(
+        "import { css } from '@sylphx/silk';\nconst button = css({ color: 'red' });",
+        Config::default(),
+    );
+
+    let first_line = code.lines().next().unwrap();
+    assert!(first_line.starts_with("/* @silk-css {"), "unexpected output:\n{}", code);
+
+    let json = &first_line["/* @silk-css ".len()..first_line.find(" */").unwrap()];
+    let parsed: serde_json::Value = serde_json::from_str(json).unwrap();
+    assert_eq!(parsed["cssRules"][0][0], metadata.class_names[0].as_str());
+    assert_eq!(parsed["classNames"].as_array().unwrap().len(), 1);
+    assert_eq!(parsed["version"], env!("CARGO_PKG_VERSION"));
+}
+
+#[test]
+fn test_css_metadata_comment_escapes_terminator() {
+    let metadata = SilkMetadata {
+        css_rules: vec![("a".into(), ".a { content: \"*/\"; }".into())],
+        class_names: vec!["a".into()],
+        version: "0.0.0".into(),
+        ..Default::default()
+    };
+
+    let text = metadata.to_comment_text();
+    assert!(!text.contains("*/"));
+
+    let json = text.trim().trim_start_matches("@silk-css ");
+    let parsed: serde_json::Value = serde_json::from_str(json).unwrap();
+    assert_eq!(parsed["cssRules"][0][1], ".a { content: \"*/\"; }");
+}
+
+#[test]
+fn test_no_comment_without_css_calls() {
+    let (code, metadata) = transform("const x = other({ bg: 'red' });", Config::default());
+
+    assert!(metadata.css_rules.is_empty());
+    assert!(!code.contains("@silk-css"));
 }
 
 #[test]
@@ -176,14 +305,16 @@ fn test_config_custom_prefix() {
 
     let config1 = Config {
         production: false,
-        class_prefix: "custom".to_string(),
+        class_prefix: Some("custom".to_string()),
+        ..Default::default()
     };
     let class1 = generate_class_name("bg", "red", &config1);
     assert!(class1.starts_with("custom_"));
 
     let config2 = Config {
         production: false,
-        class_prefix: "my-app".to_string(),
+        class_prefix: Some("my-app".to_string()),
+        ..Default::default()
     };
     let class2 = generate_class_name("bg", "red", &config2);
     assert!(class2.starts_with("my-app_"));
@@ -207,3 +338,931 @@ fn test_hash_consistency() {
     let class3 = generate_class_name("color", "red", &config);
     assert_ne!(class1a, class3);
 }
+
+#[test]
+fn test_css_import_injection() {
+    let config = Config {
+        css_output: CssOutput::Import,
+        ..Default::default()
+    };
+    let (code, metadata) = transform(
+        "'use client';\nimport { css } from '@sylphx/silk';\nconst a = css({ bg: 'red' });",
+        config,
+    );
+
+    let expected = swc_plugin_silk::css_import_specifier("virtual:silk/[hash].css", &metadata.css());
+    let lines: Vec<&str> = code.lines().collect();
+    assert!(lines[0].ends_with("'use client';"));
+    assert_eq!(lines[1], format!("import \"{}\";", expected));
+    assert!(expected.starts_with("virtual:silk/") && expected.ends_with(".css"));
+}
+
+#[test]
+fn test_css_import_only_for_modules_with_css() {
+    let config = Config {
+        css_output: CssOutput::Import,
+        ..Default::default()
+    };
+    let (code, _) = transform(
+        "import { css } from '@sylphx/silk';\nconst a = other({ bg: 'red' });",
+        config,
+    );
+
+    assert!(!code.contains("virtual:silk"));
+}
+
+#[test]
+fn test_css_import_pattern_with_inline_css() {
+    let config = Config {
+        css_output: CssOutput::Import,
+        import_pattern: "silk.css?css=[css]".to_string(),
+        ..Default::default()
+    };
+    let (code, _) = transform(
+        "import { css } from '@sylphx/silk';\nconst a = css({ color: 'red' });",
+        config,
+    );
+
+    assert!(code.contains("import \"silk.css?css=.silk_color_red_"));
+    assert!(code.contains("%7B%20color%3A%20red%3B%20%7D"));
+}
+
+#[test]
+fn test_only_imported_css_is_transformed() {
+    let (code, metadata) = transform(
+        "import { css } from '@emotion/css';\nconst a = css({ bg: 'red' });",
+        Config::default(),
+    );
+    assert!(metadata.css_rules.is_empty());
+    assert!(code.contains("css({"));
+
+    let (code, _) = transform("const a = css({ bg: 'red' });", Config::default());
+    assert!(code.contains("css({"));
+}
+
+#[test]
+fn test_aliased_and_namespace_imports() {
+    let (code, metadata) = transform(
+        "import { css as sx } from '@sylphx/silk';\nimport * as silk from '@sylphx/silk';\nconst a = sx({ bg: 'red' });\nconst b = silk.css({ color: 'blue' });\nconst c = silk.other({ color: 'green' });",
+        Config::default(),
+    );
+
+    assert_eq!(metadata.css_rules.len(), 2);
+    assert!(!code.contains("sx("));
+    assert!(!code.contains("silk.css("));
+    assert!(code.contains("silk.other("));
+}
+
+#[test]
+fn test_shadowed_binding_is_not_transformed() {
+    let (code, metadata) = transform(
+        "import { css } from '@sylphx/silk';\nfunction local(css) { return css({ bg: 'blue' }); }\nconst a = css({ bg: 'red' });",
+        Config::default(),
+    );
+
+    assert_eq!(metadata.css_rules.len(), 1);
+    assert!(metadata.class_names[0].starts_with("silk_bg_red_"));
+    assert!(code.contains("return css({"));
+}
+
+#[test]
+fn test_custom_import_sources_and_functions() {
+    let config = Config {
+        import_sources: vec!["@acme/styles".to_string()],
+        functions: vec!["sx".to_string()],
+        ..Default::default()
+    };
+    let (code, metadata) = transform(
+        "import { sx, css } from '@acme/styles';\nconst a = sx({ bg: 'red' });\nconst b = css({ bg: 'blue' });",
+        config,
+    );
+
+    assert_eq!(metadata.css_rules.len(), 1);
+    assert!(code.contains("css({"));
+}
+
+#[test]
+fn test_pseudo_selectors() {
+    let (code, metadata) = transform(
+        "import { css } from '@sylphx/silk';\nconst a = css({ color: 'red', _hover: { bg: 'blue' }, _placeholder: { color: 'gray' } });",
+        Config::default(),
+    );
+
+    assert_eq!(metadata.css_rules.len(), 3);
+    let (hover_class, hover_rule) = &metadata.css_rules[1];
+    assert!(hover_class.starts_with("silk_hover_bg_blue_"));
+    assert_eq!(hover_rule, &format!(".{}:hover {{ background-color: blue; }}", hover_class));
+
+    let (placeholder_class, placeholder_rule) = &metadata.css_rules[2];
+    assert_eq!(placeholder_rule, &format!(".{}::placeholder {{ color: gray; }}", placeholder_class));
+
+    assert!(code.contains(&metadata.class_names.join(" ")));
+}
+
+#[test]
+fn test_pseudo_variants_have_distinct_classes() {
+    let config = Config {
+        production: true,
+        ..Default::default()
+    };
+    let (_, metadata) = transform(
+        "import { css } from '@sylphx/silk';\nconst a = css({ bg: 'red', _hover: { bg: 'red' }, _focus: { bg: 'red' } });",
+        config,
+    );
+
+    assert_eq!(metadata.css_rules.len(), 3);
+}
+
+#[test]
+fn test_responsive_values() {
+    use swc_plugin_silk::css_escape;
+
+    let (code, metadata) = transform(
+        "import { css } from '@sylphx/silk';\nconst a = css({ w: { base: '100%', md: '50%' }, p: { lg: 8 } });",
+        Config::default(),
+    );
+
+    assert_eq!(metadata.css_rules.len(), 3);
+
+    let (base_class, base_rule) = &metadata.css_rules[0];
+    assert!(base_class.starts_with("silk_base_w_"));
+    // `%` from the value is escaped in the selector
+    assert_eq!(base_rule, &format!(".{} {{ width: 100%; }}", css_escape(base_class)));
+    assert!(base_rule.contains("100\\%"));
+
+    let (md_class, md_rule) = &metadata.css_rules[1];
+    assert!(md_class.starts_with("silk_md_w_"));
+    assert_eq!(md_rule, &format!("@media (min-width: 768px) {{ .{} {{ width: 50%; }} }}", css_escape(md_class)));
+
+    let (lg_class, lg_rule) = &metadata.css_rules[2];
+    assert_eq!(lg_rule, &format!("@media (min-width: 1024px) {{ .{} {{ padding: 2rem; }} }}", lg_class));
+
+    assert!(code.contains(&metadata.class_names.join(" ")));
+}
+
+#[test]
+fn test_responsive_rules_are_ordered_by_min_width() {
+    // Key order must not decide which rule wins: later rules override earlier ones
+    let (code, metadata) = transform(
+        r#"import { css } from '@sylphx/silk';
+const a = css({ w: { lg: '25%', md: '50%', base: '100%' } });
+const b = css({ h: { '40em': '50%' }, color: 'red', p: { sm: 2, base: 1 } });"#,
+        Config::default(),
+    );
+
+    let media: Vec<&str> = metadata
+        .css_rules
+        .iter()
+        .map(|(_, rule)| rule.split(" {").next().unwrap())
+        .map(|prefix| if prefix.starts_with('@') { prefix } else { "base" })
+        .collect();
+    assert_eq!(
+        media,
+        vec![
+            "base",
+            "base",
+            "base",
+            // Equal widths keep their source order
+            "@media (min-width: 40em)",
+            "@media (min-width: 640px)",
+            "@media (min-width: 768px)",
+            "@media (min-width: 1024px)",
+        ]
+    );
+    assert!(metadata.css_rules[0].1.contains("width: 100%"));
+    assert!(metadata.css_rules[1].1.contains("color: red"));
+    assert!(metadata.css_rules[2].1.contains("padding: 0.25rem"));
+
+    // Class strings keep the source order
+    let a: Vec<&str> = metadata.class_names.iter().filter(|name| name.contains("_w_")).map(String::as_str).collect();
+    assert_eq!(a.len(), 3);
+    assert!(code.contains("const a = \"silk_lg_w_"));
+}
+
+#[test]
+fn test_custom_breakpoints_and_responsive_pseudo() {
+    let mut breakpoints = std::collections::HashMap::new();
+    breakpoints.insert("tablet".to_string(), "600px".to_string());
+    let config = Config {
+        breakpoints,
+        ..Default::default()
+    };
+    let (_, metadata) = transform(
+        "import { css } from '@sylphx/silk';\nconst a = css({ _hover: { color: { tablet: 'red' } } });",
+        config,
+    );
+
+    let (class_name, rule) = &metadata.css_rules[0];
+    assert!(class_name.starts_with("silk_hover_tablet_color_red_"));
+    assert_eq!(rule, &format!("@media (min-width: 600px) {{ .{}:hover {{ color: red; }} }}", class_name));
+}
+
+#[test]
+fn test_partial_compilation() {
+    let (code, metadata) = transform(
+        "import { css } from '@sylphx/silk';\nconst a = css({ bg: 'red', color: props.color, p: 4 });",
+        Config::default(),
+    );
+
+    assert_eq!(metadata.css_rules.len(), 2);
+    let expected = format!(
+        "const a = css({{\n    color: props.color\n}}, \"{}\");",
+        metadata.class_names.join(" ")
+    );
+    assert!(code.contains(&expected), "unexpected output:\n{}", code);
+}
+
+#[test]
+fn test_partially_dynamic_nested_value_stays_at_runtime() {
+    let (code, metadata) = transform(
+        "import { css } from '@sylphx/silk';\nconst a = css({ bg: 'red', _hover: { bg: color, p: 2 }, w: { base: '100%', md: width } });",
+        Config::default(),
+    );
+
+    assert_eq!(metadata.css_rules.len(), 1);
+    assert!(code.contains("_hover: {"));
+    assert!(code.contains("md: width"));
+}
+
+#[test]
+fn test_dynamic_only_and_spread_calls_are_left_alone() {
+    let (code, metadata) = transform(
+        "import { css } from '@sylphx/silk';\nconst a = css({ color: props.color });\nconst b = css({ ...base, bg: 'red' });",
+        Config::default(),
+    );
+
+    assert!(metadata.css_rules.is_empty());
+    assert!(code.contains("css({\n    color: props.color\n})"));
+    assert!(code.contains("...base"));
+}
+
+#[test]
+fn test_empty_object_compiles_to_empty_string() {
+    let (code, _) = transform(
+        "import { css } from '@sylphx/silk';\nconst a = css({});",
+        Config::default(),
+    );
+
+    assert!(code.contains("const a = \"\";"));
+}
+
+#[test]
+fn test_signed_number_values() {
+    let (code, metadata) = transform(
+        "import { css } from '@sylphx/silk';\nconst a = css({ mt: -4, zIndex: -1, ml: +2 });",
+        Config::default(),
+    );
+
+    assert_eq!(metadata.css_rules.len(), 3);
+    let rules: Vec<&str> = metadata.css_rules.iter().map(|(_, rule)| rule.as_str()).collect();
+    assert!(rules[0].ends_with("{ margin-top: -1rem; }"));
+    assert!(rules[1].ends_with("{ z-index: -1; }"));
+    assert!(rules[2].ends_with("{ margin-left: 0.5rem; }"));
+    assert!(metadata.class_names.iter().all(|cls| !cls.contains("_-")));
+    assert!(!code.contains("css("));
+}
+
+#[test]
+fn test_constant_folding() {
+    let (code, metadata) = transform(
+        r#"import { css } from '@sylphx/silk';
+const GAP = 4;
+export const BRAND = 'blue' as const;
+const SIZE = GAP * 2;
+const a = css({
+    gap: GAP,
+    p: SIZE,
+    color: BRAND,
+    w: `${SIZE * 10}px`,
+    h: (100 + '%'),
+    maxW: `calc(100% - ${GAP}rem)` satisfies string,
+    fontFamily: 'Inter, ' + 'sans-serif',
+    m: -GAP!,
+});"#,
+        Config::default(),
+    );
+
+    let rules: Vec<&str> = metadata.css_rules.iter().map(|(_, rule)| rule.as_str()).collect();
+    assert_eq!(rules.len(), 8, "unexpected rules: {:#?}", rules);
+    assert!(rules[0].ends_with("{ gap: 1rem; }"));
+    assert!(rules[1].ends_with("{ padding: 2rem; }"));
+    assert!(rules[2].ends_with("{ color: blue; }"));
+    assert!(rules[3].ends_with("{ width: 80px; }"));
+    assert!(rules[4].ends_with("{ height: 100%; }"));
+    assert!(rules[5].ends_with("{ max-width: calc(100% - 4rem); }"));
+    assert!(rules[6].ends_with("{ font-family: Inter, sans-serif; }"));
+    assert!(rules[7].ends_with("{ margin: -1rem; }"));
+    assert!(!code.contains("css("));
+}
+
+#[test]
+fn test_non_constant_bindings_stay_dynamic() {
+    let (code, metadata) = transform(
+        r#"import { css } from '@sylphx/silk';
+let COLOR = 'red';
+function make(GAP) { return css({ gap: GAP, color: 'red' }); }
+const a = css({ color: COLOR, bg: `${COLOR}` });"#,
+        Config::default(),
+    );
+
+    assert_eq!(metadata.css_rules.len(), 1);
+    assert!(code.contains("gap: GAP"));
+    assert!(code.contains("css({\n    color: COLOR,"));
+}
+
+#[test]
+fn test_static_spreads_are_inlined() {
+    let (code, metadata) = transform(
+        r#"import { css } from '@sylphx/silk';
+const baseCard = { p: 4, bg: 'white', _hover: { bg: 'gray' } };
+const elevated = { ...baseCard, boxShadow: 'none' };
+const a = css({ ...elevated, p: 8 });"#,
+        Config::default(),
+    );
+
+    let rules: Vec<&str> = metadata.css_rules.iter().map(|(_, rule)| rule.as_str()).collect();
+    assert_eq!(rules.len(), 4, "unexpected rules: {:#?}", rules);
+    assert!(rules.iter().any(|rule| rule.ends_with("{ background-color: white; }")));
+    assert!(rules.iter().any(|rule| rule.ends_with(":hover { background-color: gray; }")));
+    assert!(rules.iter().any(|rule| rule.ends_with("{ box-shadow: none; }")));
+
+    // Last one wins: p: 8 overrides the spread p: 4
+    assert!(rules.iter().any(|rule| rule.ends_with("{ padding: 2rem; }")));
+    assert!(!rules.iter().any(|rule| rule.ends_with("{ padding: 1rem; }")));
+    assert!(!code.contains("css("));
+}
+
+#[test]
+fn test_spread_overridden_by_dynamic_value() {
+    let (code, metadata) = transform(
+        r#"import { css } from '@sylphx/silk';
+const base = { color: 'red', p: 4 };
+const a = css({ ...base, color: props.color });"#,
+        Config::default(),
+    );
+
+    assert_eq!(metadata.css_rules.len(), 1);
+    assert!(metadata.css_rules[0].1.ends_with("{ padding: 1rem; }"));
+    assert!(code.contains("color: props.color"));
+}
+
+#[test]
+fn test_non_static_spreads_stay_at_runtime() {
+    let (code, metadata) = transform(
+        r#"import { css } from '@sylphx/silk';
+let mutable = { p: 4 };
+const withDynamic = { color: theme.color };
+const a = css({ ...mutable, bg: 'red' });
+const b = css({ ...withDynamic, bg: 'red' });
+const c = css({ ...props.styles, bg: 'red' });"#,
+        Config::default(),
+    );
+
+    assert!(metadata.css_rules.is_empty());
+    assert_eq!(code.matches("css({").count(), 3);
+}
+
+#[test]
+fn test_diagnostics_point_at_dynamic_properties() {
+    let (_, metadata, diagnostics) = transform_with_diagnostics(
+        r#"import { css } from '@sylphx/silk';
+const a = css({ bg: 'red', color: props.color, [KEY]: 'blue', _hover: { _focus: { p: 2 } } });"#,
+        Config::default(),
+    );
+
+    assert_eq!(metadata.css_rules.len(), 1);
+    assert_eq!(
+        diagnostics,
+        vec![
+            (DiagnosticCode::DynamicValue, Severity::Warning, "color: props.color".to_string()),
+            (DiagnosticCode::DynamicKey, Severity::Warning, "[KEY]".to_string()),
+            (DiagnosticCode::UnsupportedNesting, Severity::Warning, "_focus: { p: 2 }".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_diagnostics_for_calls_left_at_runtime() {
+    let (_, metadata, diagnostics) = transform_with_diagnostics(
+        r#"import { css } from '@sylphx/silk';
+const a = css({ ...props.styles, bg: 'red' });
+const b = css(styles);
+const c = css({ w: { base: '100%', tablet: '50%', '900px': '25%' } });"#,
+        Config::default(),
+    );
+
+    assert_eq!(metadata.css_rules.len(), 3);
+    assert_eq!(
+        diagnostics,
+        vec![
+            (DiagnosticCode::DynamicSpread, Severity::Warning, "...props.styles".to_string()),
+            (DiagnosticCode::UnsupportedArguments, Severity::Warning, "css(styles)".to_string()),
+            (DiagnosticCode::UnknownBreakpoint, Severity::Warning, "tablet: '50%'".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_fully_static_calls_have_no_diagnostics() {
+    let (_, _, diagnostics) = transform_with_diagnostics(
+        "import { css } from '@sylphx/silk';\nconst a = css({ bg: 'red', _hover: { p: 2 }, w: { md: '50%' } });",
+        Config::default(),
+    );
+
+    assert!(diagnostics.is_empty());
+}
+
+#[test]
+fn test_strict_mode_errors_instead_of_partial_compilation() {
+    let (code, metadata, diagnostics) = transform_with_diagnostics(
+        r#"import { css } from '@sylphx/silk';
+const a = css({ bg: 'red', color: props.color });
+const b = css({ w: { base: '100%', tablet: '50%' } });"#,
+        Config {
+            strict: true,
+            ..Default::default()
+        },
+    );
+
+    assert!(code.contains("css({\n    bg: 'red',\n    color: props.color\n})"), "unexpected output:\n{}", code);
+    assert_eq!(metadata.css_rules.len(), 2);
+    assert_eq!(
+        diagnostics,
+        vec![
+            (DiagnosticCode::DynamicValue, Severity::Error, "color: props.color".to_string()),
+            // Unknown breakpoints still compile, so they stay warnings
+            (DiagnosticCode::UnknownBreakpoint, Severity::Warning, "tablet: '50%'".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_pre_scan_for_silk_imports() {
+    use swc_plugin_silk::imports_silk;
+
+    let parse = |src: &str| {
+        Tester::run(|tester| {
+            let syntax = Syntax::Typescript(TsSyntax::default());
+            Ok(Program::Module(tester.with_parser("input.ts", syntax, src, |p| p.parse_module())?))
+        })
+    };
+    let config = Config::default();
+
+    assert!(imports_silk(&parse("import { css } from '@sylphx/silk';"), &config));
+    assert!(imports_silk(&parse("import * as silk from '@sylphx/silk';"), &config));
+    assert!(!imports_silk(&parse("import type { CSSProperties } from '@sylphx/silk';"), &config));
+    assert!(!imports_silk(&parse("import { css } from '@emotion/css';\nconst a = css({ bg: 'red' });"), &config));
+}
+
+#[test]
+fn test_spans_are_preserved() {
+    use swc_core::ecma::ast::{Decl, Expr, ModuleItem, Stmt};
+
+    let src = "import { css } from '@sylphx/silk';\nconst a = css({ bg: 'red', w: { md: '50%' } });\nconst b = css({ bg: 'red', color: c });";
+    Tester::run(|tester| {
+        let syntax = Syntax::Typescript(TsSyntax::default());
+        let mut program = Program::Module(tester.with_parser("input.ts", syntax, src, |p| p.parse_module())?);
+        program.visit_mut_with(&mut resolver(Mark::new(), Mark::new(), false));
+
+        let mut visitor = SilkTransformVisitor::new(Config::default());
+        program.visit_mut_with(&mut visitor);
+
+        let snippet = |span| tester.cm.span_to_snippet(span).unwrap();
+        let Program::Module(module) = &program else { unreachable!() };
+        let init = |i: usize| match &module.body[i] {
+            ModuleItem::Stmt(Stmt::Decl(Decl::Var(var))) => var.decls[0].init.clone().unwrap(),
+            _ => unreachable!(),
+        };
+
+        // Fully static: the literal keeps the call's span
+        assert!(matches!(*init(1), Expr::Lit(_)));
+        assert_eq!(snippet(init(1).span()), "css({ bg: 'red', w: { md: '50%' } })");
+
+        // Partial: the appended class string points at the call too
+        let Expr::Call(call) = *init(2) else { unreachable!() };
+        assert_eq!(snippet(call.args[1].expr.span()), "css({ bg: 'red', color: c })");
+
+        // Every use of a rule records the property it came from and its call
+        let metadata = visitor.metadata();
+        let sources: Vec<(String, String)> = metadata
+            .rule_sources
+            .iter()
+            .map(|source| (snippet(source.span), snippet(source.call_span)))
+            .collect();
+        assert_eq!(
+            sources,
+            vec![
+                ("bg: 'red'".to_string(), "css({ bg: 'red', w: { md: '50%' } })".to_string()),
+                ("md: '50%'".to_string(), "css({ bg: 'red', w: { md: '50%' } })".to_string()),
+                ("bg: 'red'".to_string(), "css({ bg: 'red', color: c })".to_string()),
+            ]
+        );
+        assert_eq!(metadata.rule_sources[0].class_name, metadata.rule_sources[2].class_name);
+        Ok(())
+    });
+}
+
+#[test]
+fn test_dev_source_annotations() {
+    let src = "import { css } from '@sylphx/silk';\nconst a = css({ bg: 'red' });\nconst b =\n    css({ color: 'blue' });";
+    Tester::run(|tester| {
+        let syntax = Syntax::Typescript(TsSyntax::default());
+        let mut program = Program::Module(tester.with_parser("input.ts", syntax, src, |p| p.parse_module())?);
+        program.visit_mut_with(&mut resolver(Mark::new(), Mark::new(), false));
+
+        let mut visitor = SilkTransformVisitor::new(Config::default());
+        program.visit_mut_with(&mut visitor);
+
+        let mut metadata = visitor.metadata();
+        metadata.annotate_sources("src/components/Button.tsx", &*tester.cm);
+        metadata.locate_sources("src/components/Button.tsx", &*tester.cm);
+
+        // The metadata comment lists where every class is used
+        let json = metadata.to_comment_text();
+        let payload: serde_json::Value =
+            serde_json::from_str(json.trim().trim_start_matches("@silk-css ")).unwrap();
+        assert_eq!(payload["file"], "src/components/Button.tsx");
+        assert_eq!(
+            payload["sources"],
+            serde_json::json!([
+                { "className": metadata.css_rules[0].0, "line": 2, "column": 17 },
+                { "className": metadata.css_rules[1].0, "line": 4, "column": 11 },
+            ])
+        );
+
+        let [(bg, bg_rule), (color, color_rule)] = &metadata.css_rules[..] else { unreachable!() };
+        assert_eq!(
+            bg_rule,
+            &format!("/* src/components/Button.tsx:2:11 */ .{} {{ background-color: red; }}", bg)
+        );
+        assert_eq!(
+            color_rule,
+            &format!("/* src/components/Button.tsx:4:5 */ .{} {{ color: blue; }}", color)
+        );
+        Ok(())
+    });
+}
+
+#[test]
+fn test_babel_class_naming_golden_corpus() {
+    use swc_plugin_silk::{generate_variant_class_name, parse_config};
+
+    // Shared with babel-plugin-silk's test suite
+    let corpus: Vec<serde_json::Value> =
+        serde_json::from_str(include_str!("fixtures/class-names.json")).unwrap();
+    assert!(!corpus.is_empty());
+
+    for case in &corpus {
+        let mut options = case["options"].clone();
+        options["classNaming"] = "babel".into();
+        let config = parse_config(&options.to_string()).unwrap();
+
+        // Values are JavaScript strings or numbers, stringified like `String(value)`
+        let value = match &case["value"] {
+            serde_json::Value::String(s) => s.clone(),
+            number => number.as_f64().unwrap().to_string(),
+        };
+        let property = case["property"].as_str().unwrap();
+        let variant = case["variant"].as_str().unwrap();
+
+        assert_eq!(
+            generate_variant_class_name(property, &value, variant, &config),
+            case["expected"].as_str().unwrap(),
+            "case: {}",
+            case
+        );
+    }
+}
+
+#[test]
+fn test_babel_css_call_golden_corpus() {
+    use swc_plugin_silk::parse_config;
+
+    // Shared with babel-plugin-silk's test suite: css() input → class names and rules
+    let corpus: Vec<serde_json::Value> = serde_json::from_str(include_str!("fixtures/css-calls.json")).unwrap();
+    assert!(!corpus.is_empty());
+
+    for case in &corpus {
+        let mut options = case["options"].clone();
+        options["classNaming"] = "babel".into();
+        let config = parse_config(&options.to_string()).unwrap();
+
+        // JSON objects are valid object literals. serde_json sorts keys, so the corpus
+        // lists them in sorted order to keep the source order Babel sees
+        let src = format!("import {{ css }} from '@sylphx/silk';\nconst a = css({});", case["styles"]);
+        let (code, metadata) = transform(&src, config);
+
+        let class_names: Vec<String> = serde_json::from_value(case["classNames"].clone()).unwrap();
+        let css_rules: Vec<(String, String)> = serde_json::from_value(case["cssRules"].clone()).unwrap();
+        assert_eq!(metadata.css_rules, css_rules, "case: {}", case);
+        assert!(code.contains(&format!("const a = \"{}\";", class_names.join(" "))), "case: {}\n{}", case, code);
+    }
+}
+
+#[test]
+fn test_hash_salt_isolates_class_names() {
+    use std::collections::HashSet;
+    use swc_plugin_silk::{generate_variant_class_name, parse_config};
+
+    let corpus: Vec<serde_json::Value> =
+        serde_json::from_str(include_str!("fixtures/class-names.json")).unwrap();
+
+    for naming in ["compact", "babel"] {
+        for strategy in ["hash", "readable"] {
+            let names = |salt: &str| -> HashSet<String> {
+                let config = parse_config(&serde_json::json!({
+                    "classNaming": naming,
+                    "classNameStrategy": strategy,
+                    "hashSalt": salt,
+                }).to_string())
+                .unwrap();
+
+                corpus
+                    .iter()
+                    .map(|case| {
+                        let value = match &case["value"] {
+                            serde_json::Value::String(s) => s.clone(),
+                            number => number.as_f64().unwrap().to_string(),
+                        };
+                        let property = case["property"].as_str().unwrap();
+                        let variant = case["variant"].as_str().unwrap();
+                        generate_variant_class_name(property, &value, variant, &config)
+                    })
+                    .collect()
+            };
+
+            // No input under one salt may produce a name another salt uses for any input
+            let salts = ["", "shell", "checkout", "checkout-v2"];
+            for (i, a) in salts.iter().enumerate() {
+                for b in &salts[i + 1..] {
+                    let shared: Vec<_> = names(a).intersection(&names(b)).cloned().collect();
+                    assert!(shared.is_empty(), "{} {}: {:?} and {:?} share {:?}", naming, strategy, a, b, shared);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_hash_salt_scopes_sequential_names() {
+    let transform_salted = |salt: &str| {
+        let config: Config = serde_json::from_value(serde_json::json!({
+            "classNameStrategy": "sequential-minimal",
+            "hashSalt": salt,
+        }))
+        .unwrap();
+        transform("import { css } from '@sylphx/silk';\nconst a = css({ bg: 'red' });", config).1
+    };
+
+    // The same file in two apps
+    assert_ne!(transform_salted("shell").class_names, transform_salted("checkout").class_names);
+}
+
+#[test]
+fn test_dedupe_by_declaration() {
+    let src = r#"import { css } from '@sylphx/silk';
+const a = css({ bg: 'red' });
+const b = css({ bgColor: 'red', _hover: { backgroundColor: 'red' } });
+const c = css({ backgroundColor: 'red', p: 4, padding: '1rem' });"#;
+
+    let (_, metadata) = transform(src, Config::default());
+    assert_eq!(metadata.css_rules.len(), 6);
+
+    let config = Config { dedupe_by_declaration: true, ..Default::default() };
+    let (code, metadata) = transform(src, config);
+    let rules: Vec<&str> = metadata.css_rules.iter().map(|(_, rule)| rule.as_str()).collect();
+    assert_eq!(rules.len(), 3, "unexpected rules: {:#?}", rules);
+
+    let [bg, hover, padding] = &metadata.class_names[..] else {
+        panic!("unexpected classes: {:?}", metadata.class_names);
+    };
+    assert!(code.contains(&format!("const a = \"{}\";", bg)));
+    assert!(code.contains(&format!("const b = \"{} {}\";", bg, hover)));
+    assert!(code.contains(&format!("const c = \"{} {}\";", bg, padding)));
+}
+
+#[test]
+fn test_design_tokens() {
+    use swc_plugin_silk::parse_config;
+
+    let src = r#"import { css } from '@sylphx/silk';
+const a = css({ color: '$colors.primary', bg: 'colors.gray.100', p: '$spacing.4', lineHeight: '1.5' });
+const b = css({ color: '$colors.primry', _hover: { color: 'colors.primary' } });"#;
+    let tokens = r##""tokens": { "colors": { "primary": "#3b82f6", "gray": { "100": "#f3f4f6" } }, "spacing": { "4": 4 } }"##;
+
+    let config = parse_config(&format!("{{ {} }}", tokens)).unwrap();
+    let (code, metadata, diagnostics) = transform_with_diagnostics(src, config);
+    let rules: Vec<&str> = metadata.css_rules.iter().map(|(_, rule)| rule.as_str()).collect();
+    assert_eq!(rules.len(), 5, "{:#?}", rules);
+    assert!(rules[0].ends_with("{ color: #3b82f6; }"));
+    assert!(rules[1].ends_with("{ background-color: #f3f4f6; }"));
+    // Numeric tokens are normalized like numbers written in place
+    assert!(rules[2].ends_with("{ padding: 1rem; }"));
+    assert!(rules[3].ends_with("{ line-height: 1.5; }"));
+    assert!(rules[4].ends_with(":hover { color: #3b82f6; }"));
+    // Unknown tokens are left to the runtime instead of emitting invalid CSS
+    assert!(code.contains("color: '$colors.primry'"), "{}", code);
+    assert_eq!(
+        diagnostics,
+        vec![(DiagnosticCode::UnknownToken, Severity::Warning, "color: '$colors.primry'".to_string())]
+    );
+
+    let config = parse_config(&format!("{{ {}, \"strict\": true }}", tokens)).unwrap();
+    let (code, _, diagnostics) = transform_with_diagnostics(src, config);
+    assert!(code.contains("css({"), "{}", code);
+    assert_eq!(
+        diagnostics,
+        vec![(DiagnosticCode::UnknownToken, Severity::Error, "color: '$colors.primry'".to_string())]
+    );
+
+    let config = parse_config(&format!("{{ {}, \"tokenOutput\": \"var\" }}", tokens)).unwrap();
+    let (_, metadata) = transform(src, config);
+    let rules: Vec<&str> = metadata.css_rules.iter().map(|(_, rule)| rule.as_str()).collect();
+    assert!(rules[0].ends_with("{ color: var(--colors-primary); }"), "{:#?}", rules);
+    assert!(rules[1].ends_with("{ background-color: var(--colors-gray-100); }"));
+    assert!(rules[2].ends_with("{ padding: var(--spacing-4); }"));
+
+    let errors = parse_config(r#"{ "tokens": { "colors": { "evil": "red; } body {" } } }"#).unwrap_err();
+    assert_eq!(errors[0].code, DiagnosticCode::InvalidConfig);
+    assert!(errors[0].message.contains("invalid token `colors.evil`"));
+}
+
+#[test]
+fn test_spacing_scale() {
+    use swc_plugin_silk::parse_config;
+
+    let config = parse_config(r#"{"spacing": {"multiplier": 4, "unit": "px", "scale": {"xs": "4px", "sm": "8px"}}}"#).unwrap();
+    let (_, metadata) = transform(
+        "import { css } from '@sylphx/silk';\nconst a = css({ p: 4, mx: 'sm', gap: { base: 'xs', md: 2 }, top: 1, maxWidth: 100 });",
+        config,
+    );
+
+    let rules: Vec<&str> = metadata.css_rules.iter().map(|(_, rule)| rule.as_str()).collect();
+    assert_eq!(rules.len(), 6, "unexpected rules: {:#?}", rules);
+    assert!(rules[0].ends_with("{ padding: 16px; }"));
+    assert!(rules[1].ends_with("{ margin-inline: 8px; }"));
+    assert!(rules[2].ends_with("{ gap: 4px; }"));
+    assert!(rules[3].ends_with("{ top: 4px; }"));
+    assert!(rules[4].ends_with("{ max-width: 100px; }"));
+    assert!(rules[5].ends_with("{ gap: 8px; } }"));
+}
+
+#[test]
+fn test_values_that_break_out_of_the_rule_are_rejected() {
+    let (code, metadata, diagnostics) = transform_with_diagnostics(
+        r#"import { css } from '@sylphx/silk';
+const EVIL = 'red; } body { display:none';
+const a = css({ bg: 'red', color: EVIL, w: { '900px) { .x': '1px' }, content: '"}"', backgroundImage: 'url(data:image/png;base64,iVBORw0KGgo=)' });"#,
+        Config::default(),
+    );
+
+    let rules: Vec<&str> = metadata.css_rules.iter().map(|(_, rule)| rule.as_str()).collect();
+    assert_eq!(rules.len(), 3, "unexpected rules: {:#?}", rules);
+    assert!(rules[1].ends_with("{ content: \"}\"; }"));
+    // `;` inside an unquoted url() is part of the URL token
+    assert!(rules[2].ends_with("{ background-image: url(data:image/png;base64,iVBORw0KGgo=); }"));
+    assert!(code.contains("color: EVIL"));
+    assert_eq!(
+        diagnostics,
+        vec![
+            (DiagnosticCode::InvalidValue, Severity::Error, "color: EVIL".to_string()),
+            (DiagnosticCode::InvalidValue, Severity::Error, "'900px) { .x': '1px'".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_class_collisions_are_reported_once() {
+    // Both values shorten to `silk_bg_urlimghero_1akr` with Babel's 4-character hashes
+    let config: Config = serde_json::from_str(r#"{"classNaming": "babel"}"#).unwrap();
+    let src = r#"import { css } from '@sylphx/silk';
+const a = css({ bg: 'url(/img/hero-131.png)' });
+const b = css({ bg: 'url(/img/hero-495.png)' });
+const c = css({ bg: 'url(/img/hero-495.png)' });"#;
+    let (code, metadata, diagnostics) = transform_with_diagnostics(src, config);
+
+    // The later declaration gets a longer name, and keeps it for every use
+    let [(first, first_rule), (fallback, fallback_rule)] = &metadata.css_rules[..] else {
+        panic!("unexpected rules: {:#?}", metadata.css_rules);
+    };
+    assert_eq!(first, "silk_bg_urlimghero_1akr");
+    assert!(first_rule.ends_with("{ background-color: url(/img/hero-131.png); }"));
+    assert!(fallback.starts_with("silk_bg_urlimghero_1akr_"), "{}", fallback);
+    assert!(fallback_rule.ends_with("{ background-color: url(/img/hero-495.png); }"));
+    assert!(code.contains("const a = \"silk_bg_urlimghero_1akr\";"));
+    assert!(code.contains(&format!("const b = \"{}\";", fallback)));
+    assert!(code.contains(&format!("const c = \"{}\";", fallback)));
+    assert_eq!(
+        diagnostics,
+        vec![(DiagnosticCode::ClassCollision, Severity::Warning, "bg: 'url(/img/hero-495.png)'".to_string())]
+    );
+
+    // The fallback only depends on the declaration, so other modules with the clash agree on it
+    let (_, other) = transform(
+        r#"import { css } from '@sylphx/silk';
+const a = css({ p: 4, bg: 'url(/img/hero-131.png)' });
+const b = css({ color: 'red', bg: 'url(/img/hero-495.png)' });"#,
+        serde_json::from_str(r#"{"classNaming": "babel"}"#).unwrap(),
+    );
+    assert!(other.class_names.contains(fallback), "{:#?}", other.class_names);
+}
+
+#[test]
+fn test_class_collisions_across_modules_are_reported_on_merge() {
+    use swc_plugin_silk::SilkMetadata;
+
+    let config: Config = serde_json::from_str(r#"{"classNaming": "babel", "production": true}"#).unwrap();
+    let (_, mut hero) = transform(
+        r#"import { css } from '@sylphx/silk';
+const a = css({ bg: 'red', w: { md: '50%' } });"#,
+        config.clone(),
+    );
+    hero.file = Some("src/Hero.tsx".to_string());
+    let (_, footer, diagnostics) = transform_with_diagnostics(
+        r#"import { css } from '@sylphx/silk';
+const a = css({ p: 4, bg: 'red' });"#,
+        config,
+    );
+    assert!(diagnostics.is_empty());
+    // Loaders merge the payloads of `@silk-css` comments
+    let footer: SilkMetadata = serde_json::from_str(&serde_json::to_string(&footer).unwrap()).unwrap();
+
+    // A shared declaration gets one rule, and base rules stay ahead of media rules
+    let (merged, diagnostics) = SilkMetadata::merge([&hero, &footer]);
+    assert!(diagnostics.is_empty());
+    assert_eq!(merged.css_rules.len(), 3);
+    assert!(merged.css_rules[2].1.starts_with("@media (min-width: "));
+    assert_eq!(merged.class_names.len(), 3);
+
+    // Each module is fine on its own; the clash only shows up where their rules meet
+    let mut clashing = footer.clone();
+    clashing.file = Some("src/Footer.tsx".to_string());
+    let hero_bg = hero.css_rules[0].0.clone();
+    clashing.css_rules[1] = (hero_bg.clone(), format!(".{} {{ background-color: blue; }}", hero_bg));
+    let (merged, diagnostics) = SilkMetadata::merge([&hero, &clashing, &clashing]);
+    assert_eq!(merged.css_rules.len(), 3);
+    assert!(merged.css_rules[0].1.ends_with("{ background-color: red; }"));
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, DiagnosticCode::ClassCollision);
+    assert!(diagnostics[0].message.contains("src/Hero.tsx and src/Footer.tsx"));
+}
+
+#[test]
+fn test_sequential_minimal_class_names() {
+    use swc_plugin_silk::module_id;
+
+    let config: Config = serde_json::from_str(r#"{"classNameStrategy": "sequential-minimal"}"#).unwrap();
+    let (code, metadata) = transform(
+        r#"import { css } from '@sylphx/silk';
+const a = css({ bg: 'red', p: 4 });
+const b = css({ bg: 'red', _hover: { bg: 'red' } });"#,
+        config,
+    );
+
+    let module = module_id("src/input.tsx", "");
+    let class_names: Vec<String> = ["a", "b", "c"].iter().map(|ident| format!("{}{}", module, ident)).collect();
+    assert_eq!(metadata.class_names, class_names);
+    assert!(code.contains(&format!("const a = \"{} {}\";", class_names[0], class_names[1])));
+    assert!(code.contains(&format!("const b = \"{} {}\";", class_names[0], class_names[2])));
+}
+
+#[test]
+fn test_sequential_names_are_unique_across_modules() {
+    use std::collections::HashSet;
+
+    let src = "import { css } from '@sylphx/silk';\nconst a = css({ bg: 'red', p: 4, _hover: { color: 'blue' } });";
+    for options in [r#"{"classNameStrategy": "sequential-minimal"}"#, r#"{"classNameTemplate": "x[index]"}"#] {
+        let config: Config = serde_json::from_str(options).unwrap();
+
+        // Two routes with the same basename
+        let (_, home, diagnostics) = transform_file(src, config.clone(), "/app/src/app/page.tsx");
+        assert!(diagnostics.is_empty());
+        let (_, about, _) = transform_file(src, config.clone(), "/app/src/app/about/page.tsx");
+        let home: HashSet<_> = home.class_names.into_iter().collect();
+        let about: HashSet<_> = about.class_names.into_iter().collect();
+        assert_eq!(home.len(), 3);
+        assert!(home.is_disjoint(&about), "{:?} and {:?} overlap", home, about);
+
+        // A basename alone could be any of them: fall back to hashed names, which are shared
+        let (_, page, diagnostics) = transform_file(src, config.clone(), "page.tsx");
+        let (_, hashed) = transform(src, Config { class_name_strategy: Some(ClassNameStrategy::Hash), ..config });
+        if options.contains("sequential") {
+            assert_eq!(page.class_names, hashed.class_names);
+        }
+        assert!(page.class_names.iter().all(|name| !home.contains(name)));
+        assert_eq!(diagnostics, vec![(DiagnosticCode::InvalidConfig, Severity::Warning, String::new())]);
+    }
+}
+
+#[test]
+fn test_class_name_template() {
+    let config: Config = serde_json::from_str(r#"{"classNameTemplate": "[prefix]-[variant]-[prop]-[hash:6]"}"#).unwrap();
+    let (code, metadata) = transform(
+        "import { css } from '@sylphx/silk';\nconst a = css({ bg: 'red', _hover: { bg: 'blue' } });",
+        config,
+    );
+
+    assert_eq!(metadata.class_names[0], "silk-bg-oqmaqr");
+    assert!(metadata.class_names[1].starts_with("silk-hover-bg-"));
+    assert!(code.contains(&format!("const a = \"{}\";", metadata.class_names.join(" "))));
+}